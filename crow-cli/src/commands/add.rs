@@ -0,0 +1,178 @@
+use super::*;
+use clap::ValueEnum;
+use crow_core::{BuildSystemType, CrowDependencyBuild, Dependency, ManifestEditor, OutputType};
+use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::Environment;
+
+pub trait DependencyAdder {
+    fn add_dependency(&self, logger: &Logger) -> anyhow::Result<()>;
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DependencyOutputType {
+    StaticLib,
+    SharedLib,
+    HeaderOnly,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DependencyBuildSystem {
+    Crow,
+    Cmake,
+    Meson,
+    Autotools,
+    Make,
+}
+
+#[derive(Args)]
+#[command(group = clap::ArgGroup::new("source").required(true).args(["git", "path", "system"]))]
+pub struct AddCommand {
+    /// Dependency name
+    pub name: String,
+    /// Git repository URL
+    #[arg(long)]
+    pub git: Option<String>,
+    /// Local path to the dependency
+    #[arg(long)]
+    pub path: Option<String>,
+    /// pkg-config package of a library installed on the system
+    #[arg(long, value_name = "PACKAGE")]
+    pub system: Option<String>,
+    /// Version requirement of the system library, e.g. `>=1.6`
+    #[arg(long = "system-version", value_name = "REQ", requires = "system")]
+    pub system_version: Option<String>,
+    /// Git branch to track
+    #[arg(long, conflicts_with_all = ["path", "system", "tag", "rev"])]
+    pub branch: Option<String>,
+    /// Git tag to check out
+    #[arg(long, conflicts_with_all = ["path", "system", "rev"])]
+    pub tag: Option<String>,
+    /// Git commit to check out
+    #[arg(long, conflicts_with_all = ["path", "system"])]
+    pub rev: Option<String>,
+    /// Fetch git submodules of the dependency
+    #[arg(long, conflicts_with_all = ["path", "system"])]
+    pub submodules: bool,
+    /// Library name to link against
+    #[arg(long, conflicts_with = "system")]
+    pub lib_name: Option<String>,
+    /// Library type to build
+    #[arg(long, conflicts_with = "system", value_enum)]
+    pub output_type: Option<DependencyOutputType>,
+    /// Build system of the dependency (detected if omitted)
+    #[arg(long, conflicts_with = "system", value_enum)]
+    pub build_system: Option<DependencyBuildSystem>,
+    /// Extra CMake argument (can be repeated)
+    #[arg(
+        long = "cmake-option",
+        value_name = "OPTION",
+        conflicts_with = "system",
+        allow_hyphen_values = true
+    )]
+    pub cmake_options: Vec<String>,
+    /// CMake target to build and link (can be repeated)
+    #[arg(
+        long = "cmake-target",
+        value_name = "TARGET",
+        conflicts_with = "system"
+    )]
+    pub cmake_targets: Vec<String>,
+    /// Suppress output
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+}
+
+impl AddCommand {
+    fn build_config(&self) -> Option<CrowDependencyBuild> {
+        if self.lib_name.is_none()
+            && self.output_type.is_none()
+            && self.build_system.is_none()
+            && self.cmake_options.is_empty()
+            && self.cmake_targets.is_empty()
+        {
+            return None;
+        }
+
+        let mut build = CrowDependencyBuild::default();
+        if let Some(lib_name) = &self.lib_name {
+            build.lib_name = lib_name.clone();
+        }
+        if let Some(output_type) = self.output_type {
+            build.output_type = match output_type {
+                DependencyOutputType::StaticLib => OutputType::StaticLib,
+                DependencyOutputType::SharedLib => OutputType::SharedLib,
+                DependencyOutputType::HeaderOnly => OutputType::HeaderOnly,
+            };
+        }
+        build.build_system = self.build_system.map(|build_system| match build_system {
+            DependencyBuildSystem::Crow => BuildSystemType::Crow,
+            DependencyBuildSystem::Cmake => BuildSystemType::Cmake,
+            DependencyBuildSystem::Meson => BuildSystemType::Meson,
+            DependencyBuildSystem::Autotools => BuildSystemType::Autotools,
+            DependencyBuildSystem::Make => BuildSystemType::Make,
+        });
+        build.cmake_options = self.cmake_options.clone();
+        build.targets = self.cmake_targets.clone();
+        Some(build)
+    }
+
+    fn dependency(&self) -> Dependency {
+        if let Some(system) = &self.system {
+            return Dependency::System {
+                system: system.clone(),
+                version: self.system_version.clone(),
+            };
+        }
+        let build = self.build_config();
+        match (&self.git, &self.path) {
+            (Some(git), _) => Dependency::Git {
+                git: git.clone(),
+                branch: self.branch.clone().unwrap_or_default(),
+                tag: self.tag.clone(),
+                rev: self.rev.clone(),
+                submodules: self.submodules,
+                build,
+            },
+            (None, path) => Dependency::Path {
+                path: path.clone().unwrap_or_default(),
+                build,
+            },
+        }
+    }
+}
+
+impl DependencyAdder for AddCommand {
+    fn add_dependency(&self, logger: &Logger) -> Result<()> {
+        let mut editor = ManifestEditor::open("crow.toml")?;
+        let replaced = editor.has_dependency(&self.name);
+        editor.add_dependency(&self.name, &self.dependency())?;
+        editor.save()?;
+
+        let source = match (&self.git, &self.path, &self.system) {
+            (Some(git), _, _) => git.clone(),
+            (None, Some(path), _) => path.clone(),
+            (None, None, system) => format!("system {}", system.as_deref().unwrap_or_default()),
+        };
+        if replaced {
+            logger.log(
+                LogLevel::Success,
+                format!("Updated dependency '{}' ({})", self.name, source),
+                1,
+            );
+        } else {
+            logger.log(
+                LogLevel::Success,
+                format!("Added dependency '{}' ({})", self.name, source),
+                1,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Command for AddCommand {
+    fn execute(&self, logger: &mut Logger) -> Result<()> {
+        logger.quiet(Environment::quiet_mode(self.quiet));
+        self.add_dependency(logger)
+    }
+}
@@ -1,15 +1,18 @@
 use super::*;
+use crow_core::build_system::{CompilationDatabase, Workspace};
 use crow_core::Config;
 use crow_utils::Environment;
 use std::path::PathBuf;
 
 pub trait ProjectBuilder {
+    #[allow(clippy::too_many_arguments)]
     fn build_project(
         &self,
         profile: &str,
         jobs: Option<usize>,
         verbose: bool,
         global_deps: bool,
+        offline: bool,
         target: Option<&str>,
         logger: &Logger,
     ) -> anyhow::Result<std::path::PathBuf>;
@@ -33,6 +36,10 @@ pub struct BuildCommand {
     #[arg(long, default_value_t = false)]
     pub global_deps: bool,
 
+    /// Do not access the network; use dependencies already on disk
+    #[arg(long, default_value_t = false)]
+    pub offline: bool,
+
     /// Suppress output
     #[arg(short, long, default_value_t = false)]
     pub quiet: bool,
@@ -40,6 +47,14 @@ pub struct BuildCommand {
     /// Build specific target (binary or library name)
     #[arg(long)]
     pub target: Option<String>,
+
+    /// Build only these workspace members
+    #[arg(short, long = "package", value_name = "NAME")]
+    pub packages: Vec<String>,
+
+    /// Build every workspace member
+    #[arg(long, conflicts_with = "packages")]
+    pub workspace: bool,
 }
 
 impl ProjectBuilder for BuildCommand {
@@ -49,25 +64,69 @@ impl ProjectBuilder for BuildCommand {
         jobs: Option<usize>,
         verbose: bool,
         global_deps: bool,
+        offline: bool,
         target: Option<&str>,
         logger: &Logger,
     ) -> anyhow::Result<PathBuf> {
         let mut logger = logger.clone();
         logger.verbose(verbose);
 
+        let cwd = std::env::current_dir()?;
+        let workspace = Workspace::discover(&cwd)?;
+        let at_virtual_root = workspace
+            .as_ref()
+            .is_some_and(|ws| ws.member_at(&cwd).is_none());
+        if !self.packages.is_empty() || self.workspace || at_virtual_root {
+            let workspace = workspace.ok_or_else(|| {
+                anyhow::anyhow!("`--package` and `--workspace` need a crow.toml with [workspace].")
+            })?;
+            if target.is_some() {
+                anyhow::bail!("`--target` cannot be combined with workspace builds.");
+            }
+
+            let members = if self.packages.is_empty() {
+                workspace.members.iter().collect()
+            } else {
+                self.packages
+                    .iter()
+                    .map(|name| workspace.member(name))
+                    .collect::<anyhow::Result<Vec<_>>>()?
+            };
+            let outputs = workspace.build_members(
+                &members,
+                profile,
+                jobs,
+                global_deps,
+                offline,
+                logger.clone(),
+            )?;
+            return outputs
+                .into_iter()
+                .next()
+                .map(|(_, path)| path)
+                .ok_or_else(|| anyhow::anyhow!("The workspace has no members."));
+        }
+
         let config = Config::load("crow.toml")?;
         let build_system = crow_core::build_system::BuildSystem::new(
             config,
+            &cwd,
             profile,
+            jobs,
             global_deps,
+            offline,
             logger.clone(),
         )?;
 
-        if let Some(t) = target {
-            build_system.build_target(t, jobs)
+        let output = if let Some(t) = target {
+            build_system.build_target(t, jobs)?
         } else {
-            build_system.build(jobs)
-        }
+            build_system.build(jobs)?
+        };
+
+        let compdb_path = build_system.build_dir().join("compile_commands.json");
+        build_system.write_compile_commands(&compdb_path)?;
+        Ok(output)
     }
 }
 
@@ -76,11 +135,13 @@ impl Command for BuildCommand {
         logger.quiet(Environment::quiet_mode(self.quiet));
 
         let global_deps = Environment::global_deps(self.global_deps);
+        let offline = Environment::offline(self.offline);
         self.build_project(
             &self.profile,
             self.jobs,
             self.verbose,
             global_deps,
+            offline,
             self.target.as_deref(),
             logger,
         )?;
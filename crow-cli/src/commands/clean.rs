@@ -1,6 +1,8 @@
 use super::*;
+use crow_core::build_system::Workspace;
 use crow_utils::logger::{LogLevel, Logger};
 use crow_utils::Environment;
+use std::path::PathBuf;
 
 pub trait ProjectCleaner {
     fn clean_project(&self, clean_all: bool, logger: &Logger) -> anyhow::Result<()>;
@@ -18,7 +20,10 @@ pub struct CleanCommand {
 
 impl ProjectCleaner for CleanCommand {
     fn clean_project(&self, clean_all: bool, logger: &Logger) -> Result<()> {
-        let build_dir = Environment::build_dir();
+        // Members of a workspace share the target directory and dependencies of its root.
+        let root = Workspace::discover(&std::env::current_dir()?)?
+            .map_or_else(PathBuf::new, |workspace| workspace.root);
+        let build_dir = root.join(Environment::build_dir());
 
         if build_dir.exists() {
             std::fs::remove_dir_all(&build_dir)?;
@@ -36,7 +41,7 @@ impl ProjectCleaner for CleanCommand {
         }
 
         if clean_all {
-            let deps_dir = Environment::deps_dir(false);
+            let deps_dir = root.join(Environment::deps_dir(false));
             if deps_dir.exists() {
                 logger.log(
                     LogLevel::Warn,
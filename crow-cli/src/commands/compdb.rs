@@ -0,0 +1,93 @@
+use super::*;
+use crow_core::build_system::CompilationDatabase;
+use crow_core::Config;
+use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::Environment;
+use std::path::{Path, PathBuf};
+
+pub trait CompdbWriter {
+    fn write_compdb(
+        &self,
+        profile: &str,
+        output: &Path,
+        verbose: bool,
+        global_deps: bool,
+        offline: bool,
+        logger: &Logger,
+    ) -> anyhow::Result<()>;
+}
+
+#[derive(Args)]
+pub struct CompdbCommand {
+    /// Where to write the compilation database
+    #[arg(short, long, default_value = "compile_commands.json")]
+    pub output: PathBuf,
+    /// Build profile to use
+    #[arg(long, default_value = "debug")]
+    pub profile: String,
+    /// Enable verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Use global dependencies cache
+    #[arg(long, default_value_t = false)]
+    pub global_deps: bool,
+    /// Do not access the network; use dependencies already on disk
+    #[arg(long, default_value_t = false)]
+    pub offline: bool,
+    /// Suppress output
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+}
+
+impl CompdbWriter for CompdbCommand {
+    fn write_compdb(
+        &self,
+        profile: &str,
+        output: &Path,
+        verbose: bool,
+        global_deps: bool,
+        offline: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        let mut logger = logger.clone();
+        logger.verbose(verbose);
+
+        let config = Config::load("crow.toml")?;
+        let build_system = crow_core::build_system::BuildSystem::without_building(
+            config,
+            &std::env::current_dir()?,
+            profile,
+            global_deps,
+            offline,
+            logger.clone(),
+        )?;
+
+        let count = build_system.write_compile_commands(output)?;
+        logger.log(
+            LogLevel::Success,
+            format!(
+                "Wrote {} compile command(s) to '{}'",
+                count,
+                output.display()
+            ),
+            1,
+        );
+        Ok(())
+    }
+}
+
+impl Command for CompdbCommand {
+    fn execute(&self, logger: &mut Logger) -> Result<()> {
+        logger.quiet(Environment::quiet_mode(self.quiet));
+        let global_deps = Environment::global_deps(self.global_deps);
+        let offline = Environment::offline(self.offline);
+        self.write_compdb(
+            &self.profile,
+            &self.output,
+            self.verbose,
+            global_deps,
+            offline,
+            logger,
+        )
+    }
+}
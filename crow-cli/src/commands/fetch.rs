@@ -0,0 +1,74 @@
+use super::*;
+use crow_core::build_system::{BuildSystem, DependencyResolver};
+use crow_core::Config;
+use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::Environment;
+
+pub trait DependencyFetcher {
+    fn fetch_project_dependencies(
+        &self,
+        jobs: Option<usize>,
+        verbose: bool,
+        global_deps: bool,
+        logger: &Logger,
+    ) -> anyhow::Result<()>;
+}
+
+#[derive(Args)]
+pub struct FetchCommand {
+    /// Number of dependencies to fetch in parallel
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    /// Enable verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Use global dependencies cache
+    #[arg(long, default_value_t = false)]
+    pub global_deps: bool,
+    /// Suppress output
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+}
+
+impl DependencyFetcher for FetchCommand {
+    fn fetch_project_dependencies(
+        &self,
+        jobs: Option<usize>,
+        verbose: bool,
+        global_deps: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        let mut logger = logger.clone();
+        logger.verbose(verbose);
+
+        let config = Config::load("crow.toml")?;
+        logger.log(LogLevel::Bold, "Fetching dependencies...", 1);
+        let graph = BuildSystem::resolve_dependency_graph(
+            &std::env::current_dir()?,
+            &config.package.name,
+            &config.dependencies,
+            jobs,
+            global_deps,
+            false,
+            logger.clone(),
+        )?;
+        if graph.nodes.is_empty() {
+            logger.log(LogLevel::Dim, "No dependencies to fetch.", 1);
+            return Ok(());
+        }
+        logger.log(
+            LogLevel::Success,
+            format!("Fetched {} dependency source(s).", graph.nodes.len()),
+            1,
+        );
+        Ok(())
+    }
+}
+
+impl Command for FetchCommand {
+    fn execute(&self, logger: &mut Logger) -> Result<()> {
+        logger.quiet(Environment::quiet_mode(self.quiet));
+        let global_deps = Environment::global_deps(self.global_deps);
+        self.fetch_project_dependencies(self.jobs, self.verbose, global_deps, logger)
+    }
+}
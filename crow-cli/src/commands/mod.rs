@@ -2,15 +2,29 @@ use anyhow::Result;
 use clap::{Args, Subcommand};
 use crow_utils::logger::Logger;
 
+mod add;
 mod build;
 mod clean;
+mod compdb;
+mod fetch;
 mod init;
+mod remove;
 mod run;
+mod test;
+mod tree;
+mod update;
 
+pub use add::AddCommand;
 pub use build::BuildCommand;
 pub use clean::CleanCommand;
+pub use compdb::CompdbCommand;
+pub use fetch::FetchCommand;
 pub use init::InitCommand;
+pub use remove::RemoveCommand;
 pub use run::RunCommand;
+pub use test::TestCommand;
+pub use tree::TreeCommand;
+pub use update::UpdateCommand;
 
 #[derive(Subcommand)]
 pub enum Commands {
@@ -22,6 +36,20 @@ pub enum Commands {
     Clean(CleanCommand),
     /// Build and run the project
     Run(RunCommand),
+    /// Build and run the project tests
+    Test(TestCommand),
+    /// Write compile_commands.json without compiling
+    Compdb(CompdbCommand),
+    /// Move locked git dependencies to their latest revision
+    Update(UpdateCommand),
+    /// Download all dependencies without building
+    Fetch(FetchCommand),
+    /// Print the dependency tree
+    Tree(TreeCommand),
+    /// Add a dependency to crow.toml
+    Add(AddCommand),
+    /// Remove dependencies from crow.toml
+    Remove(RemoveCommand),
 }
 
 pub trait Command {
@@ -35,6 +63,13 @@ impl Command for Commands {
             Self::Build(cmd) => cmd.execute(logger),
             Self::Clean(cmd) => cmd.execute(logger),
             Self::Run(cmd) => cmd.execute(logger),
+            Self::Test(cmd) => cmd.execute(logger),
+            Self::Compdb(cmd) => cmd.execute(logger),
+            Self::Update(cmd) => cmd.execute(logger),
+            Self::Fetch(cmd) => cmd.execute(logger),
+            Self::Tree(cmd) => cmd.execute(logger),
+            Self::Add(cmd) => cmd.execute(logger),
+            Self::Remove(cmd) => cmd.execute(logger),
         }
     }
 }
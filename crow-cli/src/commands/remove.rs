@@ -0,0 +1,44 @@
+use super::*;
+use crow_core::ManifestEditor;
+use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::Environment;
+
+pub trait DependencyRemover {
+    fn remove_dependencies(&self, names: &[String], logger: &Logger) -> anyhow::Result<()>;
+}
+
+#[derive(Args)]
+pub struct RemoveCommand {
+    /// Dependencies to remove
+    #[arg(required = true)]
+    pub dependencies: Vec<String>,
+    /// Suppress output
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+}
+
+impl DependencyRemover for RemoveCommand {
+    fn remove_dependencies(&self, names: &[String], logger: &Logger) -> Result<()> {
+        let mut editor = ManifestEditor::open("crow.toml")?;
+        for name in names {
+            editor.remove_dependency(name)?;
+        }
+        editor.save()?;
+
+        for name in names {
+            logger.log(
+                LogLevel::Success,
+                format!("Removed dependency '{}'", name),
+                1,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Command for RemoveCommand {
+    fn execute(&self, logger: &mut Logger) -> Result<()> {
+        logger.quiet(Environment::quiet_mode(self.quiet));
+        self.remove_dependencies(&self.dependencies, logger)
+    }
+}
@@ -1,10 +1,12 @@
 use super::*;
 use crate::commands::build::ProjectBuilder;
+use crow_core::build_system::Workspace;
 use crow_core::Config;
 use crow_utils::logger::{LogLevel, Logger};
 use crow_utils::Environment;
 
 pub trait ProjectRunner {
+    #[allow(clippy::too_many_arguments)]
     fn run_project(
         &self,
         profile: &str,
@@ -12,6 +14,7 @@ pub trait ProjectRunner {
         jobs: Option<usize>,
         verbose: bool,
         global_deps: bool,
+        offline: bool,
         target: Option<&str>,
         logger: &Logger,
     ) -> anyhow::Result<()>;
@@ -34,12 +37,18 @@ pub struct RunCommand {
     /// Use global dependencies cache
     #[arg(long, default_value_t = false)]
     pub global_deps: bool,
+    /// Do not access the network; use dependencies already on disk
+    #[arg(long, default_value_t = false)]
+    pub offline: bool,
     /// Suppress output
     #[arg(short, long, default_value_t = false)]
     pub quiet: bool,
     /// Run specific target (binary name)
     #[arg(long)]
     pub target: Option<String>,
+    /// Workspace member to run
+    #[arg(short, long = "package", value_name = "NAME")]
+    pub package: Option<String>,
 }
 
 impl ProjectRunner for RunCommand {
@@ -50,26 +59,58 @@ impl ProjectRunner for RunCommand {
         jobs: Option<usize>,
         verbose: bool,
         global_deps: bool,
+        offline: bool,
         target: Option<&str>,
         logger: &Logger,
     ) -> Result<()> {
         let mut logger = logger.clone();
         logger.verbose(verbose);
 
+        let cwd = std::env::current_dir()?;
+        let workspace = Workspace::discover(&cwd)?;
+        if self.package.is_none()
+            && workspace
+                .as_ref()
+                .is_some_and(|ws| ws.member_at(&cwd).is_none())
+        {
+            anyhow::bail!("Choose the workspace member to run with `--package`.");
+        }
+
         let exe_path = if !no_build {
             BuildCommand {
                 profile: profile.to_string(),
                 jobs,
                 verbose,
                 global_deps,
+                offline,
                 quiet: self.quiet,
                 target: target.map(|s| s.to_string()),
+                packages: self.package.iter().cloned().collect(),
+                workspace: false,
             }
-            .build_project(profile, jobs, verbose, global_deps, target, &logger)?
+            .build_project(
+                profile,
+                jobs,
+                verbose,
+                global_deps,
+                offline,
+                target,
+                &logger,
+            )?
         } else {
-            let config = Config::load("crow.toml")?;
+            let (config, root) = match (&self.package, &workspace) {
+                (Some(name), Some(ws)) => {
+                    let member = ws.member(name)?;
+                    (member.config.clone(), member.path.clone())
+                }
+                (Some(_), None) => {
+                    anyhow::bail!("`--package` needs a crow.toml with [workspace].")
+                }
+                (None, _) => (Config::load("crow.toml")?, cwd.clone()),
+            };
             let (package_config, _, _) = crow_core::build_system::BuildSystem::resolve_config(
                 &config,
+                &root,
                 profile,
                 logger.clone(),
             )?;
@@ -80,7 +121,8 @@ impl ProjectRunner for RunCommand {
                 package_config.name
             };
 
-            let path = Environment::build_dir().join(profile).join(&exe_name);
+            let target_dir = workspace.map_or_else(Environment::build_dir, |ws| ws.target_dir());
+            let path = target_dir.join(profile).join(&exe_name);
             if !path.exists() {
                 anyhow::bail!("Executable not found at '{}'. Run `crow build --profile {}` first or remove --no-build.", path.display(), profile);
             }
@@ -101,12 +143,14 @@ impl Command for RunCommand {
     fn execute(&self, logger: &mut Logger) -> Result<()> {
         logger.quiet(Environment::quiet_mode(self.quiet));
         let global_deps = Environment::global_deps(self.global_deps);
+        let offline = Environment::offline(self.offline);
         self.run_project(
             &self.profile,
             self.no_build,
             self.jobs,
             self.verbose,
             global_deps,
+            offline,
             self.target.as_deref(),
             logger,
         )
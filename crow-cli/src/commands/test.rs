@@ -0,0 +1,149 @@
+use super::*;
+use crow_core::build_system::TestRunner;
+use crow_core::Config;
+use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::Environment;
+use std::time::Duration;
+
+pub trait ProjectTester {
+    #[allow(clippy::too_many_arguments)]
+    fn test_project(
+        &self,
+        profile: &str,
+        filter: Option<&str>,
+        no_run: bool,
+        jobs: Option<usize>,
+        verbose: bool,
+        global_deps: bool,
+        offline: bool,
+        logger: &Logger,
+    ) -> anyhow::Result<()>;
+}
+
+#[derive(Args)]
+pub struct TestCommand {
+    /// Only run tests whose name contains this string
+    pub filter: Option<String>,
+    /// Build tests but do not run them
+    #[arg(long)]
+    pub no_run: bool,
+    /// Build profile to use
+    #[arg(long, default_value = "debug")]
+    pub profile: String,
+    /// Number of parallel jobs
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    /// Enable verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Use global dependencies cache
+    #[arg(long, default_value_t = false)]
+    pub global_deps: bool,
+    /// Do not access the network; use dependencies already on disk
+    #[arg(long, default_value_t = false)]
+    pub offline: bool,
+    /// Suppress output
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+}
+
+impl ProjectTester for TestCommand {
+    fn test_project(
+        &self,
+        profile: &str,
+        filter: Option<&str>,
+        no_run: bool,
+        jobs: Option<usize>,
+        verbose: bool,
+        global_deps: bool,
+        offline: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        let mut logger = logger.clone();
+        logger.verbose(verbose);
+
+        let config = Config::load("crow.toml")?;
+        let build_system = crow_core::build_system::BuildSystem::new(
+            config,
+            &std::env::current_dir()?,
+            profile,
+            jobs,
+            global_deps,
+            offline,
+            logger.clone(),
+        )?;
+
+        let tests = build_system.build_tests(jobs, filter)?;
+        if tests.is_empty() {
+            logger.log(
+                LogLevel::Warn,
+                "No tests found. Add sources to `tests/` or `[[test]]` entries to crow.toml.",
+                1,
+            );
+            return Ok(());
+        }
+        if no_run {
+            logger.log(
+                LogLevel::Success,
+                format!("Built {} test(s).", tests.len()),
+                1,
+            );
+            return Ok(());
+        }
+
+        let results = build_system.run_tests(&tests, jobs)?;
+        let failed: Vec<&str> = results
+            .iter()
+            .filter(|r| !r.passed)
+            .map(|r| r.name.as_str())
+            .collect();
+        let passed = results.len() - failed.len();
+        let total_time: Duration = results.iter().map(|r| r.duration).sum();
+
+        if failed.is_empty() {
+            logger.log(
+                LogLevel::Success,
+                format!(
+                    "Test result: ok. {} passed; 0 failed ({:.2}s)",
+                    passed,
+                    total_time.as_secs_f64()
+                ),
+                1,
+            );
+            return Ok(());
+        }
+
+        logger.log(
+            LogLevel::Bold,
+            format!(
+                "Test result: FAILED. {} passed; {} failed ({:.2}s)",
+                passed,
+                failed.len(),
+                total_time.as_secs_f64()
+            ),
+            1,
+        );
+        for name in &failed {
+            logger.log(LogLevel::Custom("\x1b[31m"), name, 2);
+        }
+        anyhow::bail!("{} test(s) failed", failed.len());
+    }
+}
+
+impl Command for TestCommand {
+    fn execute(&self, logger: &mut Logger) -> Result<()> {
+        logger.quiet(Environment::quiet_mode(self.quiet));
+        let global_deps = Environment::global_deps(self.global_deps);
+        let offline = Environment::offline(self.offline);
+        self.test_project(
+            &self.profile,
+            self.filter.as_deref(),
+            self.no_run,
+            self.jobs,
+            self.verbose,
+            global_deps,
+            offline,
+            logger,
+        )
+    }
+}
@@ -0,0 +1,435 @@
+use super::*;
+use clap::ValueEnum;
+use crow_core::build_system::{DependencyGraph, DependencyRequester, DependencySource, Workspace};
+use crow_core::Config;
+use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::Environment;
+use std::collections::HashSet;
+
+pub trait DependencyTreePrinter {
+    fn print_tree(
+        &self,
+        format: TreeFormat,
+        invert: Option<&str>,
+        verbose: bool,
+        global_deps: bool,
+        logger: &Logger,
+    ) -> anyhow::Result<()>;
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum TreeFormat {
+    /// Indented tree
+    Text,
+    /// Graphviz dot graph
+    Dot,
+}
+
+#[derive(Args)]
+pub struct TreeCommand {
+    /// Output format
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: TreeFormat,
+    /// Show the packages that pull in this dependency
+    #[arg(short, long, value_name = "DEPENDENCY")]
+    pub invert: Option<String>,
+    /// Enable verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Use global dependencies cache
+    #[arg(long, default_value_t = false)]
+    pub global_deps: bool,
+    /// Accepted for symmetry with other commands; `crow tree` never accesses the network
+    #[arg(long, default_value_t = false, hide = true)]
+    pub offline: bool,
+    /// Suppress output
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+}
+
+impl DependencyTreePrinter for TreeCommand {
+    fn print_tree(
+        &self,
+        format: TreeFormat,
+        invert: Option<&str>,
+        verbose: bool,
+        global_deps: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        let mut logger = logger.clone();
+        logger.verbose(verbose);
+
+        let config = Config::load("crow.toml")?;
+        let root = format!("{} v{}", config.package.name, config.package.version);
+
+        // Only what is already on disk is shown; progress would be mixed into the tree.
+        let cwd = std::env::current_dir()?;
+        let mut inspect_logger = logger.clone();
+        inspect_logger.quiet(logger.quiet || !verbose);
+        let workspace = Workspace::discover(&cwd)?;
+        let graph = match &workspace {
+            Some(workspace) => DependencyGraph::inspect(
+                &workspace.root,
+                &workspace.requesters(),
+                global_deps,
+                inspect_logger,
+            )?,
+            None => DependencyGraph::inspect(
+                &cwd,
+                &[DependencyRequester {
+                    name: root.clone(),
+                    root: cwd.clone(),
+                    dependencies: &config.dependencies,
+                }],
+                global_deps,
+                inspect_logger,
+            )?,
+        };
+
+        // At the root of a virtual workspace, print the tree of every member.
+        let packages: Vec<(String, Vec<String>)> = match workspace {
+            Some(workspace) if workspace.member_at(&cwd).is_none() => workspace
+                .members
+                .iter()
+                .map(|m| {
+                    (
+                        m.name.clone(),
+                        m.config.dependencies.keys().cloned().collect(),
+                    )
+                })
+                .collect(),
+            _ => vec![(root.clone(), config.dependencies.keys().cloned().collect())],
+        };
+
+        let mut lines = Vec::new();
+        for (name, dependencies) in &packages {
+            let tree = TreeView::new(&graph, name, dependencies, invert)?;
+            lines.extend(match format {
+                TreeFormat::Text => tree.render_text(),
+                TreeFormat::Dot => tree.render_dot(),
+            });
+            if invert.is_some() {
+                break;
+            }
+        }
+        if lines.is_empty() {
+            logger.log(LogLevel::Dim, "No dependencies.", 1);
+        }
+        for line in lines {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+}
+
+impl Command for TreeCommand {
+    fn execute(&self, logger: &mut Logger) -> Result<()> {
+        logger.quiet(Environment::quiet_mode(self.quiet));
+        let global_deps = Environment::global_deps(self.global_deps);
+        self.print_tree(
+            self.format,
+            self.invert.as_deref(),
+            self.verbose,
+            global_deps,
+            logger,
+        )
+    }
+}
+
+struct TreeView<'a> {
+    graph: &'a DependencyGraph,
+    root: &'a str,
+    root_dependencies: &'a [String],
+    start: String,
+    inverted: bool,
+}
+
+impl<'a> TreeView<'a> {
+    fn new(
+        graph: &'a DependencyGraph,
+        root: &'a str,
+        root_dependencies: &'a [String],
+        invert: Option<&str>,
+    ) -> Result<Self> {
+        match invert {
+            Some(name) if !graph.nodes.contains_key(name) => {
+                anyhow::bail!("Dependency '{}' is not in the dependency tree.", name)
+            }
+            Some(name) => Ok(TreeView {
+                graph,
+                root,
+                root_dependencies,
+                start: name.to_string(),
+                inverted: true,
+            }),
+            None => Ok(TreeView {
+                graph,
+                root,
+                root_dependencies,
+                start: root.to_string(),
+                inverted: false,
+            }),
+        }
+    }
+
+    /// Dependencies of `name`, or the packages requesting it when inverted.
+    fn children(&self, name: &str) -> Vec<String> {
+        if self.inverted {
+            let mut requesters = self
+                .graph
+                .nodes
+                .get(name)
+                .map(|node| node.requested_by.clone())
+                .unwrap_or_default();
+            requesters.sort();
+            requesters.dedup();
+            requesters
+        } else if name == self.root {
+            self.root_dependencies.to_vec()
+        } else {
+            self.graph
+                .nodes
+                .get(name)
+                .map(|node| node.dependencies.clone())
+                .unwrap_or_default()
+        }
+    }
+
+    fn label(&self, name: &str) -> String {
+        let Some(node) = self.graph.nodes.get(name) else {
+            return name.to_string();
+        };
+        if let DependencySource::System { .. } = node.source {
+            return format!("{} ({})", name, node.source);
+        }
+        let build = &node.build_config;
+        let build_system = build
+            .build_system
+            .as_ref()
+            .map_or("none".to_string(), |b| b.to_string());
+        let label = format!(
+            "{} ({}) [{}, {}, lib: {}]",
+            name, node.source, build_system, build.output_type, build.lib_name
+        );
+        // `crow tree` doesn't fetch; the dependencies of a missing checkout are unknown.
+        if matches!(node.source, DependencySource::Git { .. }) && !node.source_path.exists() {
+            format!("{label} (not fetched)")
+        } else {
+            label
+        }
+    }
+
+    fn render_text(&self) -> Vec<String> {
+        if !self.inverted && self.root_dependencies.is_empty() {
+            return Vec::new();
+        }
+        let mut lines = vec![self.label(&self.start)];
+        let mut expanded = HashSet::from([self.start.clone()]);
+        self.render_children(&self.start, "", &mut expanded, &mut lines);
+        lines
+    }
+
+    fn render_children(
+        &self,
+        name: &str,
+        prefix: &str,
+        expanded: &mut HashSet<String>,
+        lines: &mut Vec<String>,
+    ) {
+        let children = self.children(name);
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let branch = if last { "└── " } else { "├── " };
+            let has_children = !self.children(child).is_empty();
+
+            // Subtrees shared by several parents are printed once and marked `(*)`.
+            if has_children && !expanded.insert(child.clone()) {
+                lines.push(format!("{prefix}{branch}{} (*)", self.label(child)));
+                continue;
+            }
+            lines.push(format!("{prefix}{branch}{}", self.label(child)));
+            let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            self.render_children(child, &child_prefix, expanded, lines);
+        }
+    }
+
+    fn render_dot(&self) -> Vec<String> {
+        let mut lines = vec!["digraph dependencies {".to_string()];
+        let mut visited = HashSet::new();
+        let mut edges = Vec::new();
+        let mut queue = vec![self.start.clone()];
+        while let Some(name) = queue.pop() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            for child in self.children(&name) {
+                // Edges always point from a package to the dependency it uses.
+                if self.inverted {
+                    edges.push((child.clone(), name.clone()));
+                } else {
+                    edges.push((name.clone(), child.clone()));
+                }
+                queue.push(child);
+            }
+        }
+
+        let mut nodes: Vec<&String> = visited.iter().collect();
+        nodes.sort();
+        for name in nodes {
+            lines.push(format!(
+                "    \"{}\" [label=\"{}\"];",
+                escape_dot(name),
+                escape_dot(&self.label(name))
+            ));
+        }
+        edges.sort();
+        for (from, to) in edges {
+            lines.push(format!(
+                "    \"{}\" -> \"{}\";",
+                escape_dot(&from),
+                escape_dot(&to)
+            ));
+        }
+        lines.push("}".to_string());
+        lines
+    }
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crow_core::build_system::DependencyNode;
+    use crow_core::{BuildSystemType, CrowDependencyBuild, Dependency};
+    use std::path::PathBuf;
+
+    fn graph() -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for (name, requested_by, dependencies) in [
+            ("net", vec!["app"], vec!["log"]),
+            ("db", vec!["app"], vec!["log"]),
+            ("log", vec!["net", "db"], vec!["fmt"]),
+            ("fmt", vec!["log"], vec![]),
+        ] {
+            graph.nodes.insert(
+                name.to_string(),
+                DependencyNode {
+                    name: name.to_string(),
+                    dependency: Dependency::Path {
+                        path: format!("../{name}"),
+                        build: None,
+                    },
+                    source: DependencySource::Path(PathBuf::from(format!("/deps/{name}"))),
+                    source_path: PathBuf::from(format!("/deps/{name}")),
+                    build_config: CrowDependencyBuild {
+                        build_system: Some(BuildSystemType::Crow),
+                        lib_name: name.to_string(),
+                        ..Default::default()
+                    },
+                    requested_by: requested_by.into_iter().map(String::from).collect(),
+                    dependencies: dependencies.into_iter().map(String::from).collect(),
+                },
+            );
+        }
+        graph
+    }
+
+    #[test]
+    fn text_marks_repeated_subtrees() {
+        let graph = graph();
+        let roots = ["net".to_string(), "db".to_string()];
+        let view = TreeView::new(&graph, "app", &roots, None).unwrap();
+        let label =
+            |name: &str| format!("{name} (path /deps/{name}) [crow, static-lib, lib: {name}]");
+        assert_eq!(
+            view.render_text(),
+            [
+                "app".to_string(),
+                format!("├── {}", label("net")),
+                format!("│   └── {}", label("log")),
+                format!("│       └── {}", label("fmt")),
+                format!("└── {}", label("db")),
+                format!("    └── {} (*)", label("log")),
+            ]
+        );
+    }
+
+    #[test]
+    fn inverted_text_lists_requesters() {
+        let graph = graph();
+        let roots = ["net".to_string(), "db".to_string()];
+        let view = TreeView::new(&graph, "app", &roots, Some("log")).unwrap();
+        let lines = view.render_text();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("├── db "));
+        assert_eq!(lines[2], "│   └── app");
+        assert!(lines[3].starts_with("└── net "));
+        assert_eq!(lines[4], "    └── app");
+    }
+
+    #[test]
+    fn inverting_an_unknown_dependency_fails() {
+        let graph = graph();
+        assert!(TreeView::new(&graph, "app", &[], Some("zlib")).is_err());
+    }
+
+    #[test]
+    fn dot_points_edges_at_dependencies() {
+        let graph = graph();
+        let roots = ["net".to_string()];
+        let lines = TreeView::new(&graph, "app", &roots, None)
+            .unwrap()
+            .render_dot();
+        assert_eq!(lines.first().unwrap(), "digraph dependencies {");
+        assert_eq!(lines.last().unwrap(), "}");
+        let edges: Vec<&String> = lines.iter().filter(|line| line.contains("->")).collect();
+        assert_eq!(
+            edges,
+            [
+                "    \"app\" -> \"net\";",
+                "    \"log\" -> \"fmt\";",
+                "    \"net\" -> \"log\";",
+            ]
+        );
+        assert_eq!(escape_dot(r#"a "b" \c"#), r#"a \"b\" \\c"#);
+    }
+
+    #[test]
+    fn missing_git_checkout_is_marked_not_fetched() {
+        let mut graph = graph();
+        graph.nodes.insert(
+            "zlib".to_string(),
+            DependencyNode {
+                name: "zlib".to_string(),
+                dependency: Dependency::Git {
+                    git: "https://example.com/zlib".to_string(),
+                    branch: String::new(),
+                    tag: None,
+                    rev: None,
+                    submodules: false,
+                    build: None,
+                },
+                source: DependencySource::Git {
+                    url: "https://example.com/zlib".to_string(),
+                    branch: String::new(),
+                    tag: String::new(),
+                    rev: String::new(),
+                },
+                source_path: PathBuf::from("/nonexistent/.crow/_deps/zlib"),
+                build_config: CrowDependencyBuild {
+                    lib_name: "zlib".to_string(),
+                    ..Default::default()
+                },
+                requested_by: vec!["app".to_string()],
+                dependencies: Vec::new(),
+            },
+        );
+        let roots = ["zlib".to_string()];
+        let view = TreeView::new(&graph, "app", &roots, None).unwrap();
+        assert!(view.render_text()[1].ends_with(" (not fetched)"));
+        assert!(!view.label("fmt").contains("not fetched"));
+    }
+}
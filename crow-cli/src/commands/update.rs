@@ -0,0 +1,136 @@
+use super::*;
+use crow_core::build_system::{BuildSystem, DependencyResolver, Workspace};
+use crow_core::{Config, Dependency, Lockfile};
+use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::Environment;
+
+pub trait DependencyUpdater {
+    fn update_dependencies(
+        &self,
+        names: &[String],
+        verbose: bool,
+        global_deps: bool,
+        logger: &Logger,
+    ) -> anyhow::Result<()>;
+}
+
+#[derive(Args)]
+pub struct UpdateCommand {
+    /// Dependencies to update (all git dependencies if omitted)
+    pub dependencies: Vec<String>,
+    /// Enable verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Use global dependencies cache
+    #[arg(long, default_value_t = false)]
+    pub global_deps: bool,
+    /// Rejected: updating needs network access
+    #[arg(long, default_value_t = false, hide = true)]
+    pub offline: bool,
+    /// Suppress output
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+}
+
+impl DependencyUpdater for UpdateCommand {
+    fn update_dependencies(
+        &self,
+        names: &[String],
+        verbose: bool,
+        global_deps: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        let mut logger = logger.clone();
+        logger.verbose(verbose);
+
+        let config = Config::load("crow.toml")?;
+        let cwd = std::env::current_dir()?;
+        let lockfile_path = Workspace::dependency_root(&cwd)?.join(Lockfile::FILE_NAME);
+        let old_lockfile = Lockfile::load(&lockfile_path)?;
+        let mut lockfile = old_lockfile.clone();
+
+        if names.is_empty() {
+            lockfile.packages.clear();
+        } else {
+            for name in names {
+                // Transitive git dependencies are pinned in the root lockfile too.
+                match config.dependencies.get(name) {
+                    Some(Dependency::Git { .. }) => {
+                        lockfile.remove(name);
+                    }
+                    Some(_) => anyhow::bail!("Dependency '{}' is not a git dependency.", name),
+                    None if lockfile.get(name).is_some() => {
+                        lockfile.remove(name);
+                    }
+                    None => anyhow::bail!(
+                        "Dependency '{}' not found in crow.toml or `{}`.",
+                        name,
+                        Lockfile::FILE_NAME
+                    ),
+                }
+            }
+        }
+        lockfile.save(&lockfile_path)?;
+
+        if let Err(e) = BuildSystem::resolve_dependency_graph(
+            &cwd,
+            &config.package.name,
+            &config.dependencies,
+            None,
+            global_deps,
+            false,
+            logger.clone(),
+        ) {
+            old_lockfile.save(&lockfile_path)?;
+            return Err(e);
+        }
+
+        let new_lockfile = Lockfile::load(&lockfile_path)?;
+        let mut updated = 0;
+        for locked in &new_lockfile.packages {
+            let old_rev = old_lockfile.get(&locked.name).map(|l| l.rev.as_str());
+            if old_rev == Some(locked.rev.as_str()) {
+                continue;
+            }
+            updated += 1;
+            logger.log(
+                LogLevel::Info,
+                format!(
+                    "[UPDATED] {} {} -> {}",
+                    locked.name,
+                    old_rev.unwrap_or("(none)"),
+                    locked.rev
+                ),
+                2,
+            );
+        }
+
+        if updated == 0 {
+            logger.log(LogLevel::Success, "Dependencies are up to date.", 1);
+        } else {
+            logger.log(
+                LogLevel::Success,
+                format!(
+                    "Updated {} dependency pin(s) in `{}`.",
+                    updated,
+                    Lockfile::FILE_NAME
+                ),
+                1,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Command for UpdateCommand {
+    fn execute(&self, logger: &mut Logger) -> Result<()> {
+        logger.quiet(Environment::quiet_mode(self.quiet));
+        if Environment::offline(self.offline) {
+            anyhow::bail!(
+                "`crow update` fetches the latest revisions from the git remotes and cannot run offline. Drop `--offline` and unset CROW_OFFLINE."
+            );
+        }
+        let global_deps = Environment::global_deps(self.global_deps);
+        self.update_dependencies(&self.dependencies, self.verbose, global_deps, logger)
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+use crate::build_system::toolchain::flags::{msvc_runtime_flag, CompilerFlavor, FlagsConverter};
+use crate::config::Language;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CompileCommand {
+    pub directory: String,
+    pub file: String,
+    pub arguments: Vec<String>,
+    pub output: String,
+}
+
+pub trait CompilationDatabase {
+    fn compile_commands(&self) -> anyhow::Result<Vec<CompileCommand>>;
+    fn write_compile_commands(&self, path: &Path) -> anyhow::Result<usize>;
+}
+
+impl CompilationDatabase for BuildSystem {
+    fn compile_commands(&self) -> anyhow::Result<Vec<CompileCommand>> {
+        let cwd = &self.root;
+        let object_dir = cwd.join(self.object_dir());
+
+        let mut packages = vec![self.package_config.clone()];
+        let mut target_names: Vec<&String> = self.config.targets.keys().collect();
+        target_names.sort();
+        for target_name in target_names {
+            packages.push(self.target_package_config(target_name)?);
+        }
+
+        let mut commands: Vec<CompileCommand> = Vec::new();
+        for package in &packages {
+            for source in utils::find_source_files(package, &self.root)? {
+                let output = BuildSystem::object_path(&object_dir, &source);
+                let source = cwd.join(&source);
+                let file = source.to_string_lossy().to_string();
+                if commands.iter().any(|c| c.file == file) {
+                    continue;
+                }
+
+                let compiler_name = self.toolchain.compiler_for(Language::from_source(&source));
+                let compiler = BuildSystem::resolve_compiler(&compiler_name)
+                    .unwrap_or_else(|| PathBuf::from(&compiler_name));
+                let flavour = BuildSystem::compiler_info(&compiler, &self.logger).flavor;
+
+                let args = BuildSystem::build_compile_args_static(
+                    &self.toolchain,
+                    &self.profile_config,
+                    package,
+                    &self.downloaded_deps_paths,
+                    &self.dep_build_outputs,
+                    &source,
+                    &output,
+                )?;
+                let args = if flavour == CompilerFlavor::MsvcLike {
+                    let mut args = BuildSystem::convert_args_for_msvc(&args, &output);
+                    args.push(msvc_runtime_flag(&self.profile_name).into());
+                    args
+                } else {
+                    args
+                };
+
+                let mut arguments = vec![compiler.to_string_lossy().to_string()];
+                arguments.extend(args.iter().map(|a| a.to_string_lossy().to_string()));
+
+                commands.push(CompileCommand {
+                    directory: cwd.to_string_lossy().to_string(),
+                    file,
+                    arguments,
+                    output: output.to_string_lossy().to_string(),
+                });
+            }
+        }
+        Ok(commands)
+    }
+
+    fn write_compile_commands(&self, path: &Path) -> anyhow::Result<usize> {
+        let commands = self.compile_commands()?;
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&commands)?)?;
+
+        if self.logger.verbose {
+            self.logger.log(
+                LogLevel::Dim,
+                format!(
+                    "Wrote {} compile command(s) to {}",
+                    commands.len(),
+                    path.display()
+                ),
+                1,
+            );
+        }
+        Ok(commands.len())
+    }
+}
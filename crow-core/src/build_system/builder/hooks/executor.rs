@@ -1,10 +1,12 @@
 use anyhow::Context;
 use crow_utils::Logger;
+use std::path::Path;
 
 pub struct Executor {}
 
 impl Executor {
-    pub fn execute_hooks(hooks: &[String], logger: Logger) -> anyhow::Result<()> {
+    /// Runs each hook in `root`, the directory of the package that declares it.
+    pub fn execute_hooks(hooks: &[String], root: &Path, logger: Logger) -> anyhow::Result<()> {
         for hook in hooks {
             let cmd = shlex::split(hook).with_context(|| format!("Cannot parse hook: '{hook}'"))?;
 
@@ -14,6 +16,7 @@ impl Executor {
 
             let output = std::process::Command::new(&cmd[0])
                 .args(&cmd[1..])
+                .current_dir(root)
                 .output()
                 .with_context(|| format!("Cannot run hook: '{hook}'"))?;
 
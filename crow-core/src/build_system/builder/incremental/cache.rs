@@ -14,13 +14,16 @@ pub struct CacheEntry {
     pub flags_hash: u64,
     #[serde(default)]
     pub deps_hash: u64,
+    /// `CompilerInfo::fingerprint` of the compiler that built the object.
+    #[serde(default)]
+    pub toolchain_fingerprint: u64,
     pub obj_path: String,
 }
 
 pub trait CacheManager {
     fn load_cache(cache_path: &Path, incremental: bool) -> anyhow::Result<BuildCache>;
     fn save_cache(cache_path: &Path, cache: &BuildCache) -> anyhow::Result<()>;
-    fn parse_dep_file(dep_path: &Path) -> anyhow::Result<Vec<PathBuf>>;
+    fn parse_dep_file(dep_path: &Path, root: &Path) -> anyhow::Result<Vec<PathBuf>>;
     fn compute_deps_hash(deps: &[PathBuf]) -> anyhow::Result<u64>;
     fn compute_flags_hash(compiler: &str, args: &[std::ffi::OsString]) -> u64;
 }
@@ -40,14 +43,14 @@ impl CacheManager for BuildCache {
         Ok(())
     }
 
-    fn parse_dep_file(dep_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    fn parse_dep_file(dep_path: &Path, root: &Path) -> anyhow::Result<Vec<PathBuf>> {
         let content = std::fs::read_to_string(dep_path)?;
         let normalized = content.replace("\\\r\n", " ").replace("\\\n", " ");
         let mut files = Vec::new();
         if let Some(pos) = normalized.find(':') {
             for part in normalized[pos + 1..].split_whitespace() {
                 if !part.is_empty() {
-                    files.push(PathBuf::from(part));
+                    files.push(root.join(part));
                 }
             }
         }
@@ -1,8 +1,9 @@
 pub mod cache;
 
 use super::*;
+use crate::build_system::toolchain::flags::FlagsConverter;
 use crate::build_system::ToolchainExecutor;
-use crate::config::PackageConfig;
+use crate::config::{Language, PackageConfig};
 use crate::utils;
 use cache::*;
 use crow_utils::LogLevel;
@@ -13,19 +14,19 @@ use std::thread;
 
 pub struct IncrementalBuilder<'a> {
     base: &'a BuildSystem,
-    build_dir: PathBuf,
+    object_dir: PathBuf,
     cache_path: PathBuf,
 }
 
 impl<'a> IncrementalBuilder<'a> {
     pub fn new(base: &'a BuildSystem) -> anyhow::Result<Self> {
-        let build_dir = crow_utils::environment::Environment::build_dir().join(&base.profile_name);
-        std::fs::create_dir_all(&build_dir)?;
+        let cache_dir = base.intermediate_dir();
+        std::fs::create_dir_all(&cache_dir)?;
 
-        let cache_path = build_dir.join(format!("crow-{}.cache", base.profile_name));
+        let cache_path = cache_dir.join(format!("crow-{}.cache", base.profile_name));
         Ok(Self {
             base,
-            build_dir,
+            object_dir: base.object_dir(),
             cache_path,
         })
     }
@@ -37,8 +38,16 @@ impl<'a> IncrementalBuilder<'a> {
     ) -> anyhow::Result<Vec<PathBuf>> {
         let old_cache =
             cache::BuildCache::load_cache(&self.cache_path, self.base.profile_config.incremental)?;
+        let sources = utils::find_source_files(package_config, &self.base.root)?;
+
+        // Keep entries of sources that are not part of this build (other targets, tests),
+        // so building one of them does not invalidate the others.
         let mut new_cache = cache::BuildCache::default();
-        let sources = utils::find_source_files(package_config)?;
+        for (key, entry) in &old_cache.entries {
+            if !sources.iter().any(|s| s.to_string_lossy() == key.as_str()) {
+                new_cache.entries.insert(key.clone(), entry.clone());
+            }
+        }
 
         let num_jobs = jobs.unwrap_or_else(|| {
             thread::available_parallelism()
@@ -47,30 +56,43 @@ impl<'a> IncrementalBuilder<'a> {
         });
         let pool = threadpool::ThreadPool::new(num_jobs);
         let (tx, rx) = mpsc::channel();
-        let mut cache_updates: HashMap<String, (u64, u64, PathBuf)> = HashMap::new();
+        let mut cache_updates: HashMap<String, (u64, u64, u64)> = HashMap::new();
 
         for source_path in &sources {
-            let obj_path = self
-                .build_dir
-                .join(source_path.with_extension("o").file_name().unwrap());
-            let source_hash = xxhash_rust::xxh3::xxh3_64(&std::fs::read(source_path)?);
-
+            let obj_path = BuildSystem::object_path(&self.object_dir, source_path);
+            if let Some(parent) = obj_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let source_hash =
+                xxhash_rust::xxh3::xxh3_64(&std::fs::read(self.base.root.join(source_path))?);
+
+            let compiler_path = self
+                .base
+                .toolchain
+                .compiler_for(Language::from_source(source_path));
+            let resolved_compiler = BuildSystem::resolve_compiler(&compiler_path)
+                .unwrap_or_else(|| PathBuf::from(&compiler_path));
+            let toolchain_fingerprint =
+                BuildSystem::compiler_info(&resolved_compiler, &self.base.logger).fingerprint;
             let args = self.base.build_compile_args(source_path, &obj_path)?;
-            let flags_hash =
-                cache::BuildCache::compute_flags_hash(&self.base.toolchain.compiler, &args);
+            let flags_hash = cache::BuildCache::compute_flags_hash(&compiler_path, &args);
 
             let source_key = source_path.to_string_lossy().to_string();
             let mut need_compile = true;
 
             if self.base.profile_config.incremental {
                 if let Some(entry) = old_cache.entries.get(&source_key) {
+                    // Objects from another compiler, or from an older object layout, are
+                    // rebuilt.
                     if entry.source_hash == source_hash
                         && entry.flags_hash == flags_hash
-                        && Path::new(&entry.obj_path).exists()
+                        && entry.toolchain_fingerprint == toolchain_fingerprint
+                        && Path::new(&entry.obj_path) == obj_path
+                        && obj_path.exists()
                     {
                         let dep_path = Path::new(&entry.obj_path).with_extension("d");
                         if dep_path.exists() {
-                            match cache::BuildCache::parse_dep_file(&dep_path)
+                            match cache::BuildCache::parse_dep_file(&dep_path, &self.base.root)
                                 .and_then(|deps| cache::BuildCache::compute_deps_hash(&deps))
                             {
                                 Ok(current_deps_hash) if entry.deps_hash == current_deps_hash => {
@@ -99,11 +121,10 @@ impl<'a> IncrementalBuilder<'a> {
 
             cache_updates.insert(
                 source_key.clone(),
-                (source_hash, flags_hash, obj_path.clone()),
+                (source_hash, flags_hash, toolchain_fingerprint),
             );
 
             let tx = tx.clone();
-            let compiler_path = self.base.toolchain.compiler.clone();
             let source_clone = source_path.clone();
             let obj_path_clone = obj_path.clone();
             let incremental = self.base.profile_config.incremental;
@@ -114,6 +135,8 @@ impl<'a> IncrementalBuilder<'a> {
             let downloaded_deps_paths_clone = self.base.downloaded_deps_paths.clone();
             let dep_build_outputs_clone = self.base.dep_build_outputs.clone();
             let logger_clone = self.base.logger.clone();
+            let root = self.base.root.clone();
+            let profile_name = self.base.profile_name.clone();
 
             pool.execute(move || {
                 let args_for_thread = BuildSystem::build_compile_args_static(
@@ -130,9 +153,11 @@ impl<'a> IncrementalBuilder<'a> {
                 let result = <BuildSystem as ToolchainExecutor>::compile_with_args(
                     &compiler_path,
                     &args_for_thread,
+                    &root,
                     &source_clone,
                     &obj_path_clone,
                     incremental,
+                    &profile_name,
                     &logger_clone,
                 );
                 if verbose_clone {
@@ -156,7 +181,7 @@ impl<'a> IncrementalBuilder<'a> {
             match result {
                 Ok((obj_path, deps_hash)) => {
                     object_files.push(obj_path.clone());
-                    if let Some((source_hash, flags_hash, _)) =
+                    if let Some((source_hash, flags_hash, toolchain_fingerprint)) =
                         cache_updates.get(&source.to_string_lossy().to_string())
                     {
                         new_cache.entries.insert(
@@ -165,6 +190,7 @@ impl<'a> IncrementalBuilder<'a> {
                                 source_hash: *source_hash,
                                 flags_hash: *flags_hash,
                                 deps_hash,
+                                toolchain_fingerprint: *toolchain_fingerprint,
                                 obj_path: obj_path.to_string_lossy().to_string(),
                             },
                         );
@@ -1,12 +1,15 @@
+pub mod compdb;
 pub mod hooks;
 pub mod incremental;
+pub mod pkg_config;
 
 use super::*;
-use crate::config::{CrowDependencyBuild, OutputType};
+use crate::config::{CrowDependencyBuild, Language, OutputType};
 use crate::utils;
 use crow_utils::LogLevel;
 use hooks::Executor;
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use pkg_config::PkgConfigWriter;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::mpsc;
@@ -19,29 +22,144 @@ pub struct BuildSystem {
     pub package_config: PackageConfig,
     pub profile_name: String,
     pub global_deps: bool,
-    pub downloaded_deps_paths: HashMap<String, PathBuf>,
-    pub dep_build_outputs: HashMap<String, DependencyBuildOutput>,
+    pub offline: bool,
+    pub downloaded_deps_paths: IndexMap<String, PathBuf>,
+    pub dep_build_outputs: IndexMap<String, DependencyBuildOutput>,
+    pub root: PathBuf,
+    pub target_dir: PathBuf,
+    pub workspace_member: Option<String>,
     pub logger: Logger,
 }
 
 impl BuildSystem {
+    /// Loads the package rooted at `root` (the directory of its `crow.toml`) and
+    /// fetches and builds its dependencies with up to `jobs` in parallel.
     pub fn new(
         config: Config,
+        root: &Path,
         profile_name: &str,
+        jobs: Option<usize>,
         global_deps: bool,
+        offline: bool,
         logger: Logger,
     ) -> anyhow::Result<Self> {
         let (package_config, toolchain, profile_config) =
-            BuildSystem::resolve_config(&config, profile_name, logger.clone())?;
+            BuildSystem::resolve_config(&config, root, profile_name, logger.clone())?;
 
         let (downloaded_deps_paths, dep_build_outputs) = BuildSystem::resolve_dependencies(
+            root,
+            &config.package.name,
             &config.dependencies,
             &toolchain,
             profile_name,
             &profile_config,
+            jobs,
             global_deps,
+            offline,
             logger.clone(),
         )?;
+        let (target_dir, workspace_member) = BuildSystem::target_layout(root)?;
+
+        Ok(Self {
+            config,
+            toolchain,
+            profile_config,
+            package_config,
+            profile_name: profile_name.to_string(),
+            global_deps,
+            offline,
+            downloaded_deps_paths,
+            dep_build_outputs,
+            root: root.to_path_buf(),
+            target_dir,
+            workspace_member,
+            logger,
+        })
+    }
+
+    /// Like `new`, but fetches the dependencies without building them. Outputs of
+    /// previous builds, header-only and system dependencies are used; every other
+    /// dependency is seen through its source directory and `include`.
+    pub fn without_building(
+        config: Config,
+        root: &Path,
+        profile_name: &str,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<Self> {
+        let (package_config, toolchain, profile_config) =
+            BuildSystem::resolve_config(&config, root, profile_name, logger.clone())?;
+
+        let roots: Vec<String> = config.dependencies.keys().cloned().collect();
+        let (downloaded_deps_paths, dep_build_outputs) = if roots.is_empty() {
+            (IndexMap::new(), IndexMap::new())
+        } else {
+            let graph = BuildSystem::resolve_dependency_graph(
+                root,
+                &config.package.name,
+                &config.dependencies,
+                None,
+                global_deps,
+                offline,
+                logger.clone(),
+            )?;
+            let mut located = BuildSystem::locate_dependency_graph(
+                &graph,
+                &roots,
+                &toolchain,
+                profile_name,
+                &profile_config,
+                global_deps,
+                offline,
+                logger.clone(),
+            )?;
+            let downloaded_deps_paths = graph
+                .build_order(&roots)?
+                .into_iter()
+                .map(|node| (node.name.clone(), node.source_path.clone()))
+                .collect();
+            let dep_build_outputs = roots
+                .iter()
+                .filter_map(|name| located.swap_remove_entry(name))
+                .collect();
+            (downloaded_deps_paths, dep_build_outputs)
+        };
+        let (target_dir, workspace_member) = BuildSystem::target_layout(root)?;
+
+        Ok(Self {
+            config,
+            toolchain,
+            profile_config,
+            package_config,
+            profile_name: profile_name.to_string(),
+            global_deps,
+            offline,
+            downloaded_deps_paths,
+            dep_build_outputs,
+            root: root.to_path_buf(),
+            target_dir,
+            workspace_member,
+            logger,
+        })
+    }
+
+    /// Like `new`, but with dependencies that were already fetched and built by the
+    /// caller, e.g. a crow dependency built from the root package's dependency graph.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_dependencies(
+        config: Config,
+        root: &Path,
+        profile_name: &str,
+        global_deps: bool,
+        offline: bool,
+        downloaded_deps_paths: IndexMap<String, PathBuf>,
+        dep_build_outputs: IndexMap<String, DependencyBuildOutput>,
+        logger: Logger,
+    ) -> anyhow::Result<Self> {
+        let (package_config, toolchain, profile_config) =
+            BuildSystem::resolve_config(&config, root, profile_name, logger.clone())?;
+        let (target_dir, workspace_member) = BuildSystem::target_layout(root)?;
 
         Ok(Self {
             config,
@@ -50,13 +168,70 @@ impl BuildSystem {
             package_config,
             profile_name: profile_name.to_string(),
             global_deps,
+            offline,
             downloaded_deps_paths,
             dep_build_outputs,
+            root: root.to_path_buf(),
+            target_dir,
+            workspace_member,
             logger,
         })
     }
 
+    /// Workspace members share the workspace `target` directory; any other package
+    /// builds into its own.
+    fn target_layout(root: &Path) -> anyhow::Result<(PathBuf, Option<String>)> {
+        Ok(match Workspace::discover(root)? {
+            Some(workspace) => {
+                let member = workspace.member_at(root).map(|m| m.name.clone());
+                (workspace.target_dir(), member)
+            }
+            None => (
+                root.join(crow_utils::environment::Environment::build_dir()),
+                None,
+            ),
+        })
+    }
+
+    /// Final artifacts of the current profile.
+    pub fn build_dir(&self) -> PathBuf {
+        self.target_dir.join(&self.profile_name)
+    }
+
+    /// Object files, laid out like the source tree. Each workspace member gets its
+    /// own, so members with equally named sources do not overwrite each other.
+    pub fn object_dir(&self) -> PathBuf {
+        let object_dir = self.build_dir().join("obj");
+        match &self.workspace_member {
+            Some(member) => object_dir.join(member),
+            None => object_dir,
+        }
+    }
+
+    /// Build caches and test binaries.
+    pub fn intermediate_dir(&self) -> PathBuf {
+        match &self.workspace_member {
+            Some(_) => self.object_dir(),
+            None => self.build_dir(),
+        }
+    }
+
     pub fn build_target(&self, target_name: &str, jobs: Option<usize>) -> anyhow::Result<PathBuf> {
+        let target_pkg = self.target_package_config(target_name)?;
+
+        self.logger.log(
+            LogLevel::Info,
+            &format!("Building target '{}'...", target_name),
+            1,
+        );
+
+        let build_output = self.build_internal(jobs, Some(&target_pkg))?;
+        Ok(build_output
+            .library_path
+            .unwrap_or(build_output.library_dir))
+    }
+
+    pub fn target_package_config(&self, target_name: &str) -> anyhow::Result<PackageConfig> {
         let target =
             self.config.targets.get(target_name).ok_or_else(|| {
                 anyhow::anyhow!("Target '{}' not found in crow.toml", target_name)
@@ -76,6 +251,12 @@ impl BuildSystem {
             .unwrap_or(&self.package_config.sources)
             .clone();
 
+        let exclude = target
+            .exclude
+            .as_ref()
+            .unwrap_or(&self.package_config.exclude)
+            .clone();
+
         let includes = target
             .includes
             .as_ref()
@@ -96,25 +277,21 @@ impl BuildSystem {
 
         let target_pkg = PackageConfig {
             name: target_name_to_use.to_string(),
-            version: "0.1.0".to_string(),
+            version: self.package_config.version.clone(),
             output_type,
             sources,
+            exclude,
             includes,
             libs,
             lib_dirs,
+            exports: self.package_config.exports.clone(),
         };
-
-        self.logger.log(
-            LogLevel::Info,
-            &format!("Building target '{}'...", target_name),
-            1,
-        );
-
-        let build_output = self.build_internal(jobs, Some(&target_pkg))?;
-        Ok(build_output.library_path)
+        Ok(target_pkg)
     }
+
     pub fn resolve_config(
         config: &Config,
+        root: &Path,
         profile_name: &str,
         logger: Logger,
     ) -> anyhow::Result<(PackageConfig, ToolchainConfig, BuildProfile)> {
@@ -135,7 +312,7 @@ impl BuildSystem {
             .hooks
             .pre_execute
             .as_ref()
-            .map(|hooks| Executor::execute_hooks(hooks, logger.clone()))
+            .map(|hooks| Executor::execute_hooks(hooks, root, logger.clone()))
             .transpose()?;
 
         let current_arch = env::consts::ARCH;
@@ -169,7 +346,7 @@ impl BuildSystem {
                     .hooks
                     .pre_execute
                     .as_ref()
-                    .map(|hooks| Executor::execute_hooks(hooks, logger.clone()))
+                    .map(|hooks| Executor::execute_hooks(hooks, root, logger.clone()))
                     .transpose()?;
 
                 target_override
@@ -180,7 +357,7 @@ impl BuildSystem {
                             .hooks
                             .pre_execute
                             .as_ref()
-                            .map(|hooks| Executor::execute_hooks(hooks, logger.clone()))
+                            .map(|hooks| Executor::execute_hooks(hooks, root, logger.clone()))
                             .transpose()?;
 
                         toolchain_override
@@ -191,6 +368,21 @@ impl BuildSystem {
                             .compiler_flags
                             .as_ref()
                             .map(|flags| toolchain.compiler_flags.clone_from(flags));
+                        if let Some(compiler) = &toolchain_override.c_compiler {
+                            toolchain.c_compiler.clone_from(compiler);
+                        }
+                        if let Some(flags) = &toolchain_override.c_flags {
+                            toolchain.c_flags.clone_from(flags);
+                        }
+                        if let Some(flags) = &toolchain_override.cxx_flags {
+                            toolchain.cxx_flags.clone_from(flags);
+                        }
+                        if let Some(std) = &toolchain_override.c_std {
+                            toolchain.c_std.clone_from(std);
+                        }
+                        if let Some(std) = &toolchain_override.cxx_std {
+                            toolchain.cxx_std.clone_from(std);
+                        }
                         toolchain_override
                             .linker
                             .as_ref()
@@ -212,7 +404,7 @@ impl BuildSystem {
                             .hooks
                             .post_execute
                             .as_ref()
-                            .map(|hooks| Executor::execute_hooks(hooks, logger.clone()))
+                            .map(|hooks| Executor::execute_hooks(hooks, root, logger.clone()))
                             .transpose()?;
 
                         Ok::<(), anyhow::Error>(())
@@ -231,6 +423,10 @@ impl BuildSystem {
                     .sources
                     .as_ref()
                     .map(|sources| package_config.sources.clone_from(sources));
+                target_override
+                    .exclude
+                    .as_ref()
+                    .map(|exclude| package_config.exclude.clone_from(exclude));
                 target_override
                     .includes
                     .as_ref()
@@ -264,7 +460,7 @@ impl BuildSystem {
                     .hooks
                     .post_execute
                     .as_ref()
-                    .map(|hooks| Executor::execute_hooks(hooks, logger.clone()))
+                    .map(|hooks| Executor::execute_hooks(hooks, root, logger.clone()))
                     .transpose()?;
 
                 Ok::<(), anyhow::Error>(())
@@ -275,7 +471,7 @@ impl BuildSystem {
             .hooks
             .post_execute
             .as_ref()
-            .map(|hooks| Executor::execute_hooks(hooks, logger.clone()))
+            .map(|hooks| Executor::execute_hooks(hooks, root, logger.clone()))
             .transpose()?;
 
         if logger.verbose && best_target_overrides.is_none() {
@@ -307,15 +503,14 @@ impl BuildSystem {
             );
         }
 
-        let build_dir = crow_utils::environment::Environment::build_dir().join(&self.profile_name);
+        let build_dir = self.build_dir();
         std::fs::create_dir_all(&build_dir)?;
-        let cwd = std::env::current_dir()?;
 
-        let object_files = if self.profile_config.incremental {
-            let incremental_builder = crate::build_system::IncrementalBuilder::new(self)?;
-            incremental_builder.build(jobs, package_config)?
+        // A header-only package has nothing to compile; it only passes on its exports.
+        let object_files = if package_config.output_type == OutputType::HeaderOnly {
+            Vec::new()
         } else {
-            self.build_non_incremental(jobs, package_config)?
+            self.compile_sources(jobs, package_config)?
         };
 
         let output_path = match package_config.output_type {
@@ -323,37 +518,97 @@ impl BuildSystem {
                 let exe_path = build_dir.join(&package_config.name);
                 self.link_executable(&object_files, &exe_path)?;
                 <BuildSystem as ToolchainExecutor>::set_executable_permissions(&exe_path)?;
-                exe_path
+                Some(exe_path)
             }
             OutputType::StaticLib => {
                 let lib_path = build_dir.join(Self::format_static_lib_name(&package_config.name));
                 self.archive_static_library(&object_files, &lib_path)?;
-                lib_path
+                Some(lib_path)
             }
             OutputType::SharedLib => {
                 let lib_path = build_dir.join(Self::format_shared_lib_name(&package_config.name));
                 self.link_shared_library(&object_files, &lib_path)?;
-                lib_path
+                Some(lib_path)
             }
+            OutputType::HeaderOnly => None,
         };
 
         if override_package_config.is_none() {
             self.logger.log(LogLevel::Success, "Build successful!", 1);
         }
 
-        let build_output = DependencyBuildOutput {
-            lib_name: package_config.name.clone(),
-            library_path: cwd.join(&output_path),
-            library_dir: cwd.join(&build_dir),
-            include_paths: package_config.includes.clone(),
-        };
+        let build_output = self.package_output(package_config, output_path);
+
+        // Non-crow consumers find the library through pkg-config.
+        if build_output.library_path.is_some()
+            && package_config.output_type != OutputType::Executable
+        {
+            let pc_path = self.write_pkg_config(package_config, &build_output)?;
+            if self.logger.verbose {
+                self.logger.log(
+                    LogLevel::Dim,
+                    format!("Wrote pkg-config file {}", pc_path.display()),
+                    2,
+                );
+            }
+        }
 
         Ok(build_output)
     }
 
+    /// What consumers of the package compile and link with, given the library it was
+    /// built into (`None` for a header-only package). Consumers get the package's public
+    /// usage requirements plus the outputs of its own dependencies, so they propagate
+    /// transitively.
+    pub fn package_output(
+        &self,
+        package_config: &PackageConfig,
+        output_path: Option<PathBuf>,
+    ) -> DependencyBuildOutput {
+        let root = &self.root;
+        let exports = &package_config.exports;
+        DependencyBuildOutput {
+            lib_name: package_config.name.clone(),
+            library_dir: match output_path {
+                Some(_) => self.build_dir(),
+                None => root.clone(),
+            },
+            library_path: output_path,
+            include_paths: exports
+                .includes
+                .as_ref()
+                .unwrap_or(&package_config.includes)
+                .iter()
+                .map(|i| root.join(i).to_string_lossy().to_string())
+                .collect(),
+            defines: exports.defines.clone(),
+            compile_flags: Vec::new(),
+            libs: exports.libs.clone(),
+            lib_dirs: exports.lib_dirs.iter().map(|d| root.join(d)).collect(),
+            link_flags: exports.link_flags.clone(),
+            // Declaration order, so that link order follows `[dependencies]`.
+            dependencies: self.dep_build_outputs.values().cloned().collect(),
+        }
+    }
+
+    pub fn compile_sources(
+        &self,
+        jobs: Option<usize>,
+        package_config: &PackageConfig,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        if self.profile_config.incremental {
+            let incremental_builder = crate::build_system::IncrementalBuilder::new(self)?;
+            incremental_builder.build(jobs, package_config)
+        } else {
+            self.build_non_incremental(jobs, package_config)
+        }
+    }
+
     pub fn build(&self, jobs: Option<usize>) -> anyhow::Result<PathBuf> {
         let build_output = self.build_internal(jobs, None)?;
-        Ok(build_output.library_path)
+        Ok(build_output
+            .library_path
+            .unwrap_or(build_output.library_dir))
     }
 
     fn build_non_incremental(
@@ -361,9 +616,8 @@ impl BuildSystem {
         jobs: Option<usize>,
         package_config: &PackageConfig,
     ) -> anyhow::Result<Vec<PathBuf>> {
-        let build_dir = crow_utils::environment::Environment::build_dir().join(&self.profile_name);
-        std::fs::create_dir_all(&build_dir)?;
-        let sources = utils::find_source_files(package_config)?;
+        let object_dir = self.object_dir();
+        let sources = utils::find_source_files(package_config, &self.root)?;
 
         let num_jobs = jobs.unwrap_or_else(|| {
             thread::available_parallelism()
@@ -376,10 +630,15 @@ impl BuildSystem {
         let mut had_errors = false;
 
         for source_path in &sources {
-            let obj_path = build_dir.join(source_path.with_extension("o").file_name().unwrap());
+            let obj_path = Self::object_path(&object_dir, source_path);
+            if let Some(parent) = obj_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
 
             let tx = tx.clone();
-            let compiler_path = self.toolchain.compiler.clone();
+            let compiler_path = self
+                .toolchain
+                .compiler_for(Language::from_source(source_path));
             let source_clone = source_path.clone();
             let obj_path_clone = obj_path.clone();
             let toolchain_clone = self.toolchain.clone();
@@ -388,6 +647,8 @@ impl BuildSystem {
             let downloaded_deps_paths_clone = self.downloaded_deps_paths.clone();
             let dep_build_outputs_clone = self.dep_build_outputs.clone();
             let logger_clone = self.logger.clone();
+            let root = self.root.clone();
+            let profile_name = self.profile_name.clone();
 
             let is_verbose = self.logger.verbose;
 
@@ -406,9 +667,11 @@ impl BuildSystem {
                 let result = <BuildSystem as ToolchainExecutor>::compile_with_args(
                     &compiler_path,
                     &args_for_thread,
+                    &root,
                     &source_clone,
                     &obj_path_clone,
                     false,
+                    &profile_name,
                     &logger_clone,
                 );
                 if is_verbose {
@@ -441,13 +704,31 @@ impl BuildSystem {
         Ok(object_files)
     }
 
+    /// Object file of `source` under `object_dir`, mirroring the source's directory.
+    /// `..` components become `__`, and absolute sources keep their full path. The
+    /// object keeps the source's extension, so `util.c` and `util.cpp` don't collide.
+    pub fn object_path(object_dir: &Path, source: &Path) -> PathBuf {
+        let mut path = object_dir.to_path_buf();
+        for component in source.components() {
+            match component {
+                std::path::Component::Normal(part) => path.push(part),
+                std::path::Component::ParentDir => path.push("__"),
+                _ => {}
+            }
+        }
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".o");
+        path.set_file_name(file_name);
+        path
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn build_compile_args_static(
         toolchain: &ToolchainConfig,
         profile: &BuildProfile,
         package: &PackageConfig,
-        downloaded_deps_paths: &HashMap<String, PathBuf>,
-        dep_build_outputs: &HashMap<String, DependencyBuildOutput>,
+        downloaded_deps_paths: &IndexMap<String, PathBuf>,
+        dep_build_outputs: &IndexMap<String, DependencyBuildOutput>,
         source: &Path,
         output: &Path,
     ) -> anyhow::Result<Vec<std::ffi::OsString>> {
@@ -458,9 +739,14 @@ impl BuildSystem {
             output.as_os_str().to_os_string(),
             std::ffi::OsString::from(format!("-O{}", profile.opt_level)),
         ];
+        let language = Language::from_source(source);
+        if let Some(std) = toolchain.std_flag(language) {
+            args.push(std.into());
+        }
         toolchain
             .compiler_flags
             .iter()
+            .chain(toolchain.language_flags(language))
             .for_each(|f| args.push(f.into()));
         if profile.lto {
             args.push("-flto".into());
@@ -470,6 +756,7 @@ impl BuildSystem {
         profile
             .defines
             .iter()
+            .chain(package.exports.defines.iter())
             .for_each(|d| args.push(format!("-D{}", d).into()));
         package
             .includes
@@ -477,16 +764,7 @@ impl BuildSystem {
             .for_each(|i| args.push(format!("-I{}", i).into()));
 
         for (dep_name, dep_root_path) in downloaded_deps_paths {
-            if let Some(build_output) = dep_build_outputs.get(dep_name) {
-                for relative_include in &build_output.include_paths {
-                    let final_include_path = if Path::new(relative_include).is_absolute() {
-                        PathBuf::from(relative_include)
-                    } else {
-                        dep_root_path.join(relative_include)
-                    };
-                    args.push(format!("-I{}", final_include_path.display()).into());
-                }
-            } else {
+            if !dep_build_outputs.contains_key(dep_name) {
                 args.push(format!("-I{}", dep_root_path.display()).into());
                 let dep_include_path = dep_root_path.join("include");
                 if dep_include_path.exists() {
@@ -495,6 +773,18 @@ impl BuildSystem {
             }
         }
 
+        for build_output in DependencyBuildOutput::flatten(dep_build_outputs.values()) {
+            for include in &build_output.include_paths {
+                args.push(format!("-I{}", include).into());
+            }
+            for define in &build_output.defines {
+                args.push(format!("-D{}", define).into());
+            }
+            for flag in &build_output.compile_flags {
+                args.push(flag.into());
+            }
+        }
+
         if profile.incremental {
             let dep_path = output.with_extension("d");
             args.push("-MMD".into());
@@ -550,7 +840,7 @@ impl BuildSystem {
                 .arg(&pch_file_path)
                 .arg("-o")
                 .arg(&pch_output_path)
-                .arg("-std=c++17")
+                .args(toolchain.std_flag(Language::Cxx))
                 .arg(format!("-O{}", profile_config.opt_level));
 
             pch_cmd.stderr(Stdio::piped());
@@ -591,8 +881,7 @@ impl BuildSystem {
         dep_source_dir: &Path,
         build_dir: &Path,
         build_type: &str,
-        toolchain: &ToolchainConfig,
-        cxx_flags_str: &str,
+        toolchain_file: &Path,
         cmake_options: &[String],
         logger: Logger,
     ) -> anyhow::Result<()> {
@@ -612,11 +901,12 @@ impl BuildSystem {
                 .arg("-B")
                 .arg(build_dir)
                 .arg(format!("-DCMAKE_BUILD_TYPE={}", build_type))
-                .arg(format!("-DCMAKE_CXX_COMPILER={}", &toolchain.compiler))
-                .arg(format!("-DCMAKE_CXX_FLAGS={}", cxx_flags_str))
+                .arg(format!(
+                    "-DCMAKE_TOOLCHAIN_FILE={}",
+                    toolchain_file.display()
+                ))
                 .arg("-DCMAKE_DEBUG_POSTFIX=")
                 .arg("-DBUILD_TESTING=OFF")
-                .arg("-DCMAKE_MSVC_RUNTIME_LIBRARY='MultiThreaded'")
                 .arg("-DCMAKE_DISABLE_TESTING=ON");
 
             for opt in cmake_options {
@@ -667,6 +957,7 @@ impl BuildSystem {
         name: &str,
         build_dir: &Path,
         build_type: &str,
+        targets: &[String],
         logger: Logger,
     ) -> anyhow::Result<()> {
         let mut build_cmd = std::process::Command::new("cmake");
@@ -675,6 +966,9 @@ impl BuildSystem {
             .arg(build_dir)
             .arg("--config")
             .arg(build_type);
+        if !targets.is_empty() {
+            build_cmd.arg("--target").args(targets);
+        }
         if logger.verbose {
             logger.log(
                 LogLevel::Dim,
@@ -714,3 +1008,42 @@ impl BuildSystem {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_path_mirrors_the_source_tree() {
+        let object_dir = Path::new("target/debug/obj");
+        assert_eq!(
+            BuildSystem::object_path(object_dir, Path::new("src/net/util.cpp")),
+            Path::new("target/debug/obj/src/net/util.cpp.o")
+        );
+        assert_eq!(
+            BuildSystem::object_path(object_dir, Path::new("./src/main.c")),
+            Path::new("target/debug/obj/src/main.c.o")
+        );
+        assert_ne!(
+            BuildSystem::object_path(object_dir, Path::new("src/util.c")),
+            BuildSystem::object_path(object_dir, Path::new("src/util.cpp"))
+        );
+    }
+
+    #[test]
+    fn object_path_keeps_sources_outside_the_package_apart() {
+        let object_dir = Path::new("obj");
+        assert_eq!(
+            BuildSystem::object_path(object_dir, Path::new("../shared/util.cpp")),
+            Path::new("obj/__/shared/util.cpp.o")
+        );
+        assert_ne!(
+            BuildSystem::object_path(object_dir, Path::new("../util.cpp")),
+            BuildSystem::object_path(object_dir, Path::new("util.cpp"))
+        );
+        assert_eq!(
+            BuildSystem::object_path(object_dir, Path::new("/opt/src/util.cpp")),
+            Path::new("obj/opt/src/util.cpp.o")
+        );
+    }
+}
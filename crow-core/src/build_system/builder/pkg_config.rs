@@ -0,0 +1,232 @@
+use super::*;
+
+pub trait PkgConfigWriter {
+    fn pkg_config_contents(
+        &self,
+        package_config: &PackageConfig,
+        build_output: &DependencyBuildOutput,
+    ) -> anyhow::Result<String>;
+    fn write_pkg_config(
+        &self,
+        package_config: &PackageConfig,
+        build_output: &DependencyBuildOutput,
+    ) -> anyhow::Result<PathBuf>;
+}
+
+impl PkgConfigWriter for BuildSystem {
+    /// A `.pc` file for the library in `build_output`. `Cflags` and `Libs` hold what the
+    /// package exports; the libraries of its dependencies, and for a static build its own
+    /// `libs`, go into `Libs.private`.
+    fn pkg_config_contents(
+        &self,
+        package_config: &PackageConfig,
+        build_output: &DependencyBuildOutput,
+    ) -> anyhow::Result<String> {
+        let prefix = std::path::absolute(&self.root)?;
+        // Relative paths are relative to the working directory, as on the command line.
+        let path = |path: &Path| {
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+            match path.strip_prefix(&prefix) {
+                Ok(relative) => escape(&format!("${{prefix}}/{}", relative.display())),
+                Err(_) => escape(&path.display().to_string()),
+            }
+        };
+        let dependencies = DependencyBuildOutput::flatten(&build_output.dependencies);
+
+        let mut cflags = Vec::new();
+        for output in std::iter::once(build_output).chain(&dependencies) {
+            for include in &output.include_paths {
+                cflags.push(format!("-I{}", path(Path::new(include))));
+            }
+            for define in &output.defines {
+                cflags.push(format!("-D{}", escape(define)));
+            }
+            cflags.extend(output.compile_flags.iter().map(|flag| escape(flag)));
+        }
+
+        let mut libs = vec![
+            "-L${libdir}".to_string(),
+            format!("-l{}", build_output.lib_name),
+        ];
+        libs.extend(link_inputs(build_output, &path));
+
+        // A shared library already links its own libs; a static archive leaves them to
+        // the consumer.
+        let mut libs_private = Vec::new();
+        if package_config.output_type == OutputType::StaticLib {
+            for lib_dir in &package_config.lib_dirs {
+                libs_private.push(format!("-L{}", path(&self.root.join(lib_dir))));
+            }
+            for lib in &package_config.libs {
+                libs_private.push(format!("-l{lib}"));
+            }
+        }
+        for output in &dependencies {
+            if output.library_path.is_some() {
+                libs_private.push(format!("-L{}", path(&output.library_dir)));
+                libs_private.push(format!("-l{}", output.lib_name));
+            }
+            libs_private.extend(link_inputs(output, &path));
+        }
+
+        let mut contents = format!(
+            "prefix={}\nlibdir={}\n\nName: {name}\nDescription: {name} library built by crow\nVersion: {}\n",
+            escape(&prefix.display().to_string()),
+            path(&build_output.library_dir),
+            package_config.version,
+            name = package_config.name,
+        );
+        for (field, flags) in [
+            ("Cflags", cflags),
+            ("Libs", libs),
+            ("Libs.private", libs_private),
+        ] {
+            let flags = dedup(flags);
+            if !flags.is_empty() {
+                contents.push_str(&format!("{field}: {}\n", flags.join(" ")));
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Writes `<name>.pc` next to the library and returns its path.
+    fn write_pkg_config(
+        &self,
+        package_config: &PackageConfig,
+        build_output: &DependencyBuildOutput,
+    ) -> anyhow::Result<PathBuf> {
+        let pc_path = build_output
+            .library_dir
+            .join(format!("{}.pc", package_config.name));
+        std::fs::write(
+            &pc_path,
+            self.pkg_config_contents(package_config, build_output)?,
+        )?;
+        Ok(pc_path)
+    }
+}
+
+/// `-L`, `-l` and other link flags an output exports besides its own library.
+fn link_inputs(output: &DependencyBuildOutput, path: &impl Fn(&Path) -> String) -> Vec<String> {
+    let lib_dirs = output.lib_dirs.iter().map(|dir| format!("-L{}", path(dir)));
+    let libs = output.libs.iter().map(|lib| format!("-l{lib}"));
+    let link_flags = output.link_flags.iter().map(|flag| escape(flag));
+    lib_dirs.chain(libs).chain(link_flags).collect()
+}
+
+/// Keeps the first of repeated `-I`, `-D` and `-L` flags; libraries keep every
+/// occurrence, as their order matters to the linker.
+fn dedup(flags: Vec<String>) -> Vec<String> {
+    let mut kept: Vec<String> = Vec::new();
+    for flag in flags {
+        let repeatable = !["-I", "-D", "-L"].iter().any(|p| flag.starts_with(p));
+        if repeatable || !kept.contains(&flag) {
+            kept.push(flag);
+        }
+    }
+    kept
+}
+
+/// pkg-config splits fields on whitespace unless it is escaped.
+fn escape(value: &str) -> String {
+    value.replace(' ', "\\ ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_system(root: &Path, output_type: OutputType) -> BuildSystem {
+        let mut config = Config::default();
+        config.package.name = "geo".to_string();
+        config.package.version = "1.2.0".to_string();
+        config.package.output_type = output_type;
+        config.package.lib_dirs = vec!["vendor".into()];
+        config.package.libs = vec!["z".to_string()];
+        BuildSystem::with_dependencies(
+            config,
+            root,
+            "debug",
+            false,
+            false,
+            IndexMap::new(),
+            IndexMap::new(),
+            Logger::new(),
+        )
+        .unwrap()
+    }
+
+    fn build_output(root: &Path) -> DependencyBuildOutput {
+        let mut math = DependencyBuildOutput::new(
+            "math".to_string(),
+            root.join("deps/math/libmath.a"),
+            vec![root.join("deps/math/include").display().to_string()],
+        );
+        math.defines = vec!["MATH_STATIC".to_string()];
+        math.compile_flags = vec!["-pthread".to_string()];
+        math.libs = vec!["m".to_string()];
+
+        let mut geo = DependencyBuildOutput::new(
+            "geo".to_string(),
+            root.join("out/libgeo.a"),
+            vec![root.join("include").display().to_string()],
+        );
+        geo.defines = vec!["GEO_NAME=\"my geo\"".to_string()];
+        geo.dependencies = vec![math];
+        geo
+    }
+
+    #[test]
+    fn static_library_lists_its_own_libs_as_private() {
+        let root = std::env::temp_dir().join(format!("crow-pc-static-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let build_system = build_system(&root, OutputType::StaticLib);
+
+        let contents = build_system
+            .pkg_config_contents(&build_system.package_config, &build_output(&root))
+            .unwrap();
+        assert_eq!(
+            contents,
+            format!(
+                "prefix={}\nlibdir=${{prefix}}/out\n\n\
+                 Name: geo\nDescription: geo library built by crow\nVersion: 1.2.0\n\
+                 Cflags: -I${{prefix}}/include -DGEO_NAME=\"my\\ geo\" \
+                 -I${{prefix}}/deps/math/include -DMATH_STATIC -pthread\n\
+                 Libs: -L${{libdir}} -lgeo\n\
+                 Libs.private: -L${{prefix}}/vendor -lz -L${{prefix}}/deps/math -lmath -lm\n",
+                std::path::absolute(&root).unwrap().display()
+            )
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn shared_library_keeps_its_own_libs_out_of_libs_private() {
+        let root = std::env::temp_dir().join(format!("crow-pc-shared-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let build_system = build_system(&root, OutputType::SharedLib);
+
+        let contents = build_system
+            .pkg_config_contents(&build_system.package_config, &build_output(&root))
+            .unwrap();
+        assert!(contents.contains("Libs.private: -L${prefix}/deps/math -lmath -lm\n"));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn dedup_keeps_repeated_libraries() {
+        let flags = [
+            "-Ia", "-DX", "-Ia", "-La", "-lz", "-La", "-DX", "-lz", "-pthread",
+        ];
+        assert_eq!(
+            dedup(flags.iter().map(|f| f.to_string()).collect()),
+            vec!["-Ia", "-DX", "-La", "-lz", "-lz", "-pthread"]
+        );
+    }
+
+    #[test]
+    fn escape_protects_spaces() {
+        assert_eq!(escape("/opt/my libs/include"), "/opt/my\\ libs/include");
+        assert_eq!(escape("plain"), "plain");
+    }
+}
@@ -0,0 +1,840 @@
+use crate::build_system;
+use crate::build_system::DependencyResolver;
+use crate::build_system::SystemDependency;
+use crate::config::{
+    BuildSystemType, Config, CrowDependencyBuild, Dependency, LockedDependency, Lockfile,
+};
+use crow_utils::logger::{LogLevel, Logger};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencySource {
+    Git {
+        url: String,
+        branch: String,
+        tag: String,
+        rev: String,
+    },
+    Path(PathBuf),
+    System {
+        package: String,
+        version: String,
+    },
+}
+
+impl DependencySource {
+    pub fn from_dependency(dep: &Dependency, root: &Path) -> Self {
+        match dep {
+            Dependency::Git {
+                git,
+                branch,
+                tag,
+                rev,
+                ..
+            } => DependencySource::Git {
+                url: git.clone(),
+                branch: branch.clone(),
+                tag: tag.clone().unwrap_or_default(),
+                rev: rev.clone().unwrap_or_default(),
+            },
+            Dependency::Path { path, .. } => {
+                let path = root.join(path);
+                DependencySource::Path(std::fs::canonicalize(&path).unwrap_or(path))
+            }
+            Dependency::System { system, version } => DependencySource::System {
+                package: system.clone(),
+                version: version.clone().unwrap_or_default(),
+            },
+        }
+    }
+
+    /// Two requests can share one checkout if they name the same repository and
+    /// revision. URLs are compared without a trailing `/` or `.git`, and a short `rev`
+    /// matches the full hash it abbreviates.
+    pub fn same_as(&self, other: &DependencySource) -> bool {
+        match (self, other) {
+            (
+                DependencySource::Git {
+                    url,
+                    branch,
+                    tag,
+                    rev,
+                },
+                DependencySource::Git {
+                    url: other_url,
+                    branch: other_branch,
+                    tag: other_tag,
+                    rev: other_rev,
+                },
+            ) => {
+                normalize_git_url(url) == normalize_git_url(other_url)
+                    && branch == other_branch
+                    && tag == other_tag
+                    && (rev == other_rev
+                        || (!rev.is_empty()
+                            && !other_rev.is_empty()
+                            && (rev.starts_with(other_rev.as_str())
+                                || other_rev.starts_with(rev.as_str()))))
+            }
+            (DependencySource::Path(path), DependencySource::Path(other_path)) => {
+                path == other_path
+            }
+            (
+                DependencySource::System { package, version },
+                DependencySource::System {
+                    package: other_package,
+                    version: other_version,
+                },
+            ) => package == other_package && version == other_version,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for DependencySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencySource::Git {
+                url,
+                branch,
+                tag,
+                rev,
+            } => {
+                write!(f, "git {url}")?;
+                if !tag.is_empty() {
+                    write!(f, " (tag {tag})")
+                } else if !rev.is_empty() {
+                    write!(f, " (rev {rev})")
+                } else if !branch.is_empty() {
+                    write!(f, " (branch {branch})")
+                } else {
+                    write!(f, " (default branch)")
+                }
+            }
+            DependencySource::Path(path) => write!(f, "path {}", path.display()),
+            DependencySource::System { package, version } if version.is_empty() => {
+                write!(f, "system {package}")
+            }
+            DependencySource::System { package, version } => {
+                write!(f, "system {package} ({version})")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DependencyNode {
+    pub name: String,
+    pub dependency: Dependency,
+    pub source: DependencySource,
+    pub source_path: PathBuf,
+    pub build_config: CrowDependencyBuild,
+    pub requested_by: Vec<String>,
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    pub roots: Vec<String>,
+    pub nodes: IndexMap<String, DependencyNode>,
+}
+
+/// A package whose `[dependencies]` seed the graph: the root package, or every member
+/// of a workspace.
+pub struct DependencyRequester<'a> {
+    pub name: String,
+    pub root: PathBuf,
+    pub dependencies: &'a IndexMap<String, Dependency>,
+}
+
+impl DependencyGraph {
+    /// Fetches every dependency of the given packages into the dependencies directory of
+    /// `store_root`, with one lockfile. A dependency requested by several packages is
+    /// fetched and built once; requests for the same name with different sources are
+    /// reported together. New dependencies of one level of the tree are fetched with up
+    /// to `jobs` in parallel.
+    pub fn collect(
+        store_root: &Path,
+        requesters: &[DependencyRequester],
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<Self> {
+        Self::gather(
+            store_root,
+            requesters,
+            jobs,
+            global_deps,
+            offline,
+            false,
+            logger,
+        )
+    }
+
+    /// The graph as it is on disk, for commands that only inspect it. Nothing is fetched,
+    /// checked out or copied, and the lockfile is left as it is. A git dependency that
+    /// was never fetched has a `source_path` that does not exist and no dependencies.
+    pub fn inspect(
+        store_root: &Path,
+        requesters: &[DependencyRequester],
+        global_deps: bool,
+        logger: Logger,
+    ) -> anyhow::Result<Self> {
+        Self::gather(
+            store_root,
+            requesters,
+            None,
+            global_deps,
+            true,
+            true,
+            logger,
+        )
+    }
+
+    fn gather(
+        store_root: &Path,
+        requesters: &[DependencyRequester],
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        read_only: bool,
+        logger: Logger,
+    ) -> anyhow::Result<Self> {
+        let deps_download_dir =
+            store_root.join(crow_utils::environment::Environment::deps_dir(global_deps));
+        if !read_only {
+            std::fs::create_dir_all(&deps_download_dir)?;
+        }
+
+        let lockfile_path = store_root.join(Lockfile::FILE_NAME);
+        let mut fetcher = Fetcher {
+            deps_download_dir,
+            lockfile: Lockfile::load(&lockfile_path)?,
+            new_lockfile: Lockfile::default(),
+            global_deps,
+            offline,
+            read_only,
+            git_checked: offline,
+            logger: logger.clone(),
+        };
+
+        let mut graph = DependencyGraph::default();
+        let mut conflicts: HashMap<String, Vec<(DependencySource, String)>> = HashMap::new();
+        let mut queue: VecDeque<Request> = VecDeque::new();
+        for requester in requesters {
+            for (name, dep) in requester.dependencies {
+                queue.push_back((
+                    requester.name.clone(),
+                    requester.root.clone(),
+                    name.clone(),
+                    dep.clone(),
+                ));
+                if !graph.roots.contains(name) {
+                    graph.roots.push(name.clone());
+                }
+            }
+        }
+
+        let num_jobs = jobs.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
+        while !queue.is_empty() {
+            let level: Vec<Request> = queue.drain(..).collect();
+            let mut fetched = fetcher.fetch_level(&level, &graph, num_jobs)?;
+
+            for (requester, requester_root, name, dep) in level {
+                let source = DependencySource::from_dependency(&dep, &requester_root);
+
+                if let Some(node) = graph.nodes.get_mut(&name) {
+                    if node.source.same_as(&source) {
+                        if logger.verbose {
+                            logger.log(
+                                LogLevel::Dim,
+                                format!("Dependency '{name}' requested by '{requester}' is shared"),
+                                1,
+                            );
+                        }
+                        node.requested_by.push(requester);
+                    } else {
+                        conflicts
+                            .entry(name)
+                            .or_insert_with(|| {
+                                vec![(node.source.clone(), node.requested_by.join(", "))]
+                            })
+                            .push((source, requester));
+                    }
+                    continue;
+                }
+
+                let (source_path, locked) = fetched
+                    .remove(&name)
+                    .expect("every new dependency of a level is fetched")?;
+                if let Some(locked) = locked {
+                    fetcher.new_lockfile.packages.push(locked);
+                }
+                let build_config = match dep {
+                    // A system library is used as installed; there is nothing to infer.
+                    Dependency::System { .. } => CrowDependencyBuild {
+                        lib_name: name.clone(),
+                        ..Default::default()
+                    },
+                    _ => CrowDependencyBuild::infer_defaults(
+                        &source_path,
+                        &name,
+                        dep.build_config().cloned(),
+                    ),
+                };
+
+                let mut node_dependencies = Vec::new();
+                if build_config.build_system == Some(BuildSystemType::Crow) {
+                    let dep_crow_toml = source_path.join("crow.toml");
+                    if dep_crow_toml.exists() {
+                        let dep_config = Config::load(&dep_crow_toml)?;
+                        // Relative paths are relative to where a path dependency is, not
+                        // to its copy in the global cache.
+                        let dep_root = match &source {
+                            DependencySource::Path(path) => path.clone(),
+                            _ => source_path.clone(),
+                        };
+                        for (child, child_dep) in &dep_config.dependencies {
+                            queue.push_back((
+                                name.clone(),
+                                dep_root.clone(),
+                                child.clone(),
+                                child_dep.clone(),
+                            ));
+                            node_dependencies.push(child.clone());
+                        }
+                    }
+                }
+
+                graph.nodes.insert(
+                    name.clone(),
+                    DependencyNode {
+                        name,
+                        dependency: dep,
+                        source,
+                        source_path,
+                        build_config,
+                        requested_by: vec![requester],
+                        dependencies: node_dependencies,
+                    },
+                );
+            }
+        }
+
+        if !conflicts.is_empty() {
+            anyhow::bail!(conflict_report(conflicts));
+        }
+
+        fetcher
+            .lockfile
+            .packages
+            .sort_by(|a, b| a.name.cmp(&b.name));
+        fetcher
+            .new_lockfile
+            .packages
+            .sort_by(|a, b| a.name.cmp(&b.name));
+        if !read_only && fetcher.new_lockfile != fetcher.lockfile {
+            fetcher.new_lockfile.save(&lockfile_path)?;
+            if logger.verbose {
+                logger.log(
+                    LogLevel::Dim,
+                    format!("Updated `{}`", Lockfile::FILE_NAME),
+                    1,
+                );
+            }
+        }
+        Ok(graph)
+    }
+
+    /// The nodes reachable from `roots`, ordered so that every dependency comes before
+    /// the packages that use it.
+    pub fn build_order(&self, roots: &[String]) -> anyhow::Result<Vec<&DependencyNode>> {
+        fn visit<'a>(
+            graph: &'a DependencyGraph,
+            name: &str,
+            stack: &mut Vec<String>,
+            done: &mut HashSet<String>,
+            ordered: &mut Vec<&'a DependencyNode>,
+        ) -> anyhow::Result<()> {
+            if done.contains(name) {
+                return Ok(());
+            }
+            if let Some(start) = stack.iter().position(|n| n == name) {
+                let mut cycle = stack[start..].to_vec();
+                cycle.push(name.to_string());
+                anyhow::bail!("Dependency cycle detected: {}", cycle.join(" -> "));
+            }
+
+            let node = &graph.nodes[name];
+            stack.push(name.to_string());
+            for child in &node.dependencies {
+                visit(graph, child, stack, done, ordered)?;
+            }
+            stack.pop();
+            done.insert(name.to_string());
+            ordered.push(node);
+            Ok(())
+        }
+
+        let mut done = HashSet::new();
+        let mut ordered = Vec::new();
+        for name in roots {
+            visit(self, name, &mut Vec::new(), &mut done, &mut ordered)?;
+        }
+        Ok(ordered)
+    }
+}
+
+struct Fetcher {
+    deps_download_dir: PathBuf,
+    lockfile: Lockfile,
+    new_lockfile: Lockfile,
+    global_deps: bool,
+    offline: bool,
+    /// Only look at what is on disk; see [`DependencyGraph::inspect`].
+    read_only: bool,
+    git_checked: bool,
+    logger: Logger,
+}
+
+/// A dependency to add to the graph: requester name and root, dependency name and spec.
+type Request = (String, PathBuf, String, Dependency);
+
+type Fetched = anyhow::Result<(PathBuf, Option<LockedDependency>)>;
+
+impl Fetcher {
+    /// Fetches the dependencies of `level` that are not in `graph` yet, on up to `jobs`
+    /// threads. A name requested more than once is fetched for its first request.
+    fn fetch_level(
+        &mut self,
+        level: &[Request],
+        graph: &DependencyGraph,
+        jobs: usize,
+    ) -> anyhow::Result<HashMap<String, Fetched>> {
+        let mut requests: Vec<&Request> = Vec::new();
+        for request in level {
+            let name = &request.2;
+            if !graph.nodes.contains_key(name) && requests.iter().all(|r| r.2 != *name) {
+                requests.push(request);
+            }
+        }
+
+        if !self.git_checked
+            && requests
+                .iter()
+                .any(|(_, _, _, dep)| matches!(dep, Dependency::Git { .. }))
+        {
+            <build_system::BuildSystem as build_system::GitManager>::check_git_available()?;
+            self.git_checked = true;
+        }
+
+        let next = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..jobs.min(requests.len()) {
+                let (tx, next, requests) = (tx.clone(), &next, &requests);
+                let fetcher = &*self;
+                scope.spawn(move || {
+                    while let Some((_, root, name, dep)) =
+                        requests.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let _ = tx.send((name.clone(), fetcher.fetch(name, dep, root)));
+                    }
+                });
+            }
+        });
+        drop(tx);
+        Ok(rx.into_iter().collect())
+    }
+
+    fn fetch(
+        &self,
+        name: &str,
+        dep: &Dependency,
+        root: &Path,
+    ) -> anyhow::Result<(PathBuf, Option<LockedDependency>)> {
+        let logger = &self.logger;
+        let offline = self.offline;
+
+        match dep {
+            Dependency::Git {
+                git,
+                branch,
+                tag,
+                rev,
+                submodules,
+                ..
+            } => {
+                let refs_count = [!branch.is_empty(), tag.is_some(), rev.is_some()]
+                    .iter()
+                    .filter(|set| **set)
+                    .count();
+                if refs_count > 1 {
+                    anyhow::bail!(
+                        "Dependency '{}' sets more than one of `branch`, `tag` and `rev`.",
+                        name
+                    );
+                }
+
+                let git_dep_target_path = self.deps_download_dir.join(name);
+                if self.read_only {
+                    return Ok((git_dep_target_path, None));
+                }
+                let tag = tag.clone().unwrap_or_default();
+                let reference = if tag.is_empty() { branch } else { &tag };
+                let locked = self.lockfile.get(name).filter(|l| {
+                    l.git == *git
+                        && l.branch == *branch
+                        && l.tag == tag
+                        && rev.as_ref().is_none_or(|r| l.rev.starts_with(r.as_str()))
+                });
+                let wanted_rev = locked.map(|l| l.rev.as_str()).or(rev.as_deref());
+
+                if offline && !git_dep_target_path.exists() {
+                    anyhow::bail!(
+                        "Dependency '{}' is not available offline: '{}' does not exist. Run `crow fetch` while online first.",
+                        name,
+                        git_dep_target_path.display()
+                    );
+                } else if !git_dep_target_path.exists() {
+                    if logger.verbose {
+                        logger.log(
+                            LogLevel::Dim,
+                            format!("Cloning new dependency '{name}' from {}", git),
+                            1,
+                        );
+                    } else {
+                        logger.log(
+                            LogLevel::Custom("\x1b[32m"),
+                            format!("[CLONING] {name} ({})", git),
+                            2,
+                        );
+                    }
+                    <build_system::BuildSystem as build_system::GitManager>::git_clone(
+                        git,
+                        reference,
+                        &git_dep_target_path,
+                        &logger.clone(),
+                    )?;
+                } else if wanted_rev.is_none() && offline {
+                    logger.log(
+                        LogLevel::Info,
+                        format!("[OFFLINE] {name} (using local checkout)"),
+                        2,
+                    );
+                } else if wanted_rev.is_none() {
+                    if logger.verbose {
+                        logger.log(
+                            LogLevel::Dim,
+                            format!("Dependency '{name}' exists. Fetching updates..."),
+                            1,
+                        );
+                    } else {
+                        logger.log(LogLevel::Info, format!("[UPDATING] {name} ({})", git), 2);
+                    }
+                    <build_system::BuildSystem as build_system::GitManager>::git_fetch_latest(
+                        &git_dep_target_path,
+                        reference,
+                        logger,
+                    )?;
+                }
+
+                if let Some(wanted_rev) = wanted_rev {
+                    let head =
+                        <build_system::BuildSystem as build_system::GitManager>::git_head_rev(
+                            &git_dep_target_path,
+                        )?;
+                    if !head.starts_with(wanted_rev)
+                        && offline
+                        && !<build_system::BuildSystem as build_system::GitManager>::git_has_rev(
+                            &git_dep_target_path,
+                            wanted_rev,
+                        )
+                    {
+                        anyhow::bail!(
+                            "Dependency '{}' is not available offline: revision {} is not in '{}'. Run `crow fetch` while online first.",
+                            name,
+                            short_rev(wanted_rev),
+                            git_dep_target_path.display()
+                        );
+                    } else if !head.starts_with(wanted_rev) {
+                        logger.log(
+                            LogLevel::Info,
+                            format!("[CHECKOUT] {name} ({})", short_rev(wanted_rev)),
+                            2,
+                        );
+                        <build_system::BuildSystem as build_system::GitManager>::git_checkout_rev(
+                            &git_dep_target_path,
+                            wanted_rev,
+                            logger,
+                        )?;
+                    } else if logger.verbose {
+                        logger.log(
+                            LogLevel::Dim,
+                            format!(
+                                "Dependency '{name}' is at revision {}",
+                                short_rev(wanted_rev)
+                            ),
+                            1,
+                        );
+                    }
+                }
+
+                if *submodules {
+                    <build_system::BuildSystem as build_system::GitManager>::git_update_submodules(
+                        &git_dep_target_path,
+                        offline,
+                        logger,
+                    )?;
+                }
+
+                let rev = <build_system::BuildSystem as build_system::GitManager>::git_head_rev(
+                    &git_dep_target_path,
+                )?;
+                let locked = LockedDependency {
+                    name: name.to_string(),
+                    git: git.clone(),
+                    branch: branch.clone(),
+                    tag,
+                    rev,
+                };
+                Ok((git_dep_target_path, Some(locked)))
+            }
+            Dependency::Path { path, .. } => {
+                let local_path_orig = root.join(path);
+                if !local_path_orig.exists() {
+                    anyhow::bail!(
+                        "Local dependency path '{}' for '{}' does not exist.",
+                        local_path_orig.display(),
+                        name
+                    );
+                }
+
+                if self.global_deps && !self.read_only {
+                    let global_local_dep_target_path = self.deps_download_dir.join(name);
+                    build_system::BuildSystem::copy_local_dependency(
+                        name,
+                        &local_path_orig,
+                        &global_local_dep_target_path,
+                        logger.clone(),
+                    )?;
+                    Ok((global_local_dep_target_path, None))
+                } else {
+                    if logger.verbose {
+                        logger.log(
+                            LogLevel::Dim,
+                            format!(
+                                "Using local dependency '{name}' from {}",
+                                local_path_orig.display()
+                            ),
+                            1,
+                        );
+                    } else {
+                        logger.log(
+                            LogLevel::Info,
+                            format!("[LOCAL] {name} ({})", local_path_orig.display()),
+                            2,
+                        );
+                    }
+                    Ok((std::fs::canonicalize(&local_path_orig)?, None))
+                }
+            }
+            Dependency::System { .. } if self.read_only => Ok((PathBuf::new(), None)),
+            Dependency::System { system, version } => {
+                let pc_dir = SystemDependency::new(system, version.as_deref()).probe(name)?;
+                if logger.verbose {
+                    logger.log(
+                        LogLevel::Dim,
+                        format!(
+                            "Using system library '{system}' for '{name}' from {}",
+                            pc_dir.display()
+                        ),
+                        1,
+                    );
+                } else {
+                    logger.log(LogLevel::Info, format!("[SYSTEM] {name} ({system})"), 2);
+                }
+                Ok((pc_dir, None))
+            }
+        }
+    }
+}
+
+fn conflict_report(conflicts: HashMap<String, Vec<(DependencySource, String)>>) -> String {
+    let mut names: Vec<&String> = conflicts.keys().collect();
+    names.sort();
+
+    let mut report = String::from("Conflicting dependency requirements:");
+    for name in names {
+        // Group the requesters of each distinct source under one line.
+        let mut sources: Vec<(&DependencySource, Vec<&str>)> = Vec::new();
+        for (source, requester) in &conflicts[name] {
+            match sources.iter_mut().find(|(s, _)| s.same_as(source)) {
+                Some((_, requesters)) => requesters.push(requester),
+                None => sources.push((source, vec![requester])),
+            }
+        }
+
+        report.push_str(&format!("\n  '{name}' is requested as:"));
+        for (source, requesters) in sources {
+            report.push_str(&format!(
+                "\n    {source}\n      required by: {}",
+                requesters.join(", ")
+            ));
+        }
+    }
+    report.push_str(
+        "\nEvery package in the tree must use the same source and revision for a dependency.",
+    );
+    report
+}
+
+fn normalize_git_url(url: &str) -> &str {
+    let url = url.trim_end_matches('/');
+    url.strip_suffix(".git").unwrap_or(url)
+}
+
+fn short_rev(rev: &str) -> &str {
+    &rev[..rev.len().min(8)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_source(name: &str) -> DependencySource {
+        DependencySource::Path(PathBuf::from(format!("/deps/{name}")))
+    }
+
+    fn graph(edges: &[(&str, &[&str])]) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for (name, dependencies) in edges {
+            graph.nodes.insert(
+                name.to_string(),
+                DependencyNode {
+                    name: name.to_string(),
+                    dependency: Dependency::Path {
+                        path: format!("../{name}"),
+                        build: None,
+                    },
+                    source: path_source(name),
+                    source_path: PathBuf::from(format!("/deps/{name}")),
+                    build_config: CrowDependencyBuild::default(),
+                    requested_by: vec!["app".to_string()],
+                    dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+                },
+            );
+        }
+        graph
+    }
+
+    fn names(nodes: Vec<&DependencyNode>) -> Vec<&str> {
+        nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+
+    #[test]
+    fn build_order_puts_dependencies_first_once() {
+        let graph = graph(&[
+            ("app_lib", &["net", "log"]),
+            ("net", &["log"]),
+            ("log", &[]),
+        ]);
+        let order = graph.build_order(&["app_lib".to_string()]).unwrap();
+        assert_eq!(names(order), ["log", "net", "app_lib"]);
+    }
+
+    #[test]
+    fn build_order_reports_cycles() {
+        let graph = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+        let error = graph.build_order(&["a".to_string()]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Dependency cycle detected: a -> b -> c -> a"
+        );
+    }
+
+    #[test]
+    fn git_sources_match_despite_url_suffix_and_short_rev() {
+        let git = |url: &str, rev: &str| DependencySource::Git {
+            url: url.to_string(),
+            branch: String::new(),
+            tag: String::new(),
+            rev: rev.to_string(),
+        };
+        assert!(git("https://example.com/fmt.git", "4447c75")
+            .same_as(&git("https://example.com/fmt/", "4447c7562e3bc702")));
+        assert!(!git("https://example.com/fmt", "4447c75")
+            .same_as(&git("https://example.com/fmt", "1234567")));
+        assert!(!git("https://example.com/fmt", "").same_as(&path_source("fmt")));
+    }
+
+    #[test]
+    fn conflict_report_groups_requesters_by_source() {
+        let mut conflicts = HashMap::new();
+        conflicts.insert(
+            "log".to_string(),
+            vec![
+                (path_source("log"), "net".to_string()),
+                (path_source("log"), "db".to_string()),
+                (
+                    DependencySource::Path(PathBuf::from("/other/log")),
+                    "app".to_string(),
+                ),
+            ],
+        );
+        assert_eq!(
+            conflict_report(conflicts),
+            "Conflicting dependency requirements:\n  \
+             'log' is requested as:\n    \
+             path /deps/log\n      required by: net, db\n    \
+             path /other/log\n      required by: app\n\
+             Every package in the tree must use the same source and revision for a dependency."
+        );
+    }
+
+    #[test]
+    fn inspect_leaves_the_project_untouched() {
+        let root = std::env::temp_dir().join(format!("crow-graph-inspect-{}", std::process::id()));
+        let util = root.join("util");
+        std::fs::create_dir_all(&util).unwrap();
+        std::fs::write(
+            util.join("crow.toml"),
+            "[package]\nname = \"util\"\n\n[dependencies]\nfmt = { git = \"https://example.invalid/fmt\" }\n",
+        )
+        .unwrap();
+        let config: Config = toml::from_str(
+            "[dependencies]\nutil = { path = \"util\" }\nlog = { git = \"https://example.invalid/log\" }\n",
+        )
+        .unwrap();
+
+        let graph = DependencyGraph::inspect(
+            &root,
+            &[DependencyRequester {
+                name: "app".to_string(),
+                root: root.clone(),
+                dependencies: &config.dependencies,
+            }],
+            false,
+            Logger::new(),
+        )
+        .unwrap();
+        assert_eq!(graph.nodes["util"].dependencies, ["fmt"]);
+        assert!(!graph.nodes["log"].source_path.exists());
+        assert!(!graph.nodes["fmt"].source_path.exists());
+        assert!(!root.join(Lockfile::FILE_NAME).exists());
+        assert!(!root.join(".crow").exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
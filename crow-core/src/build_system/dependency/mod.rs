@@ -1,4 +1,9 @@
+pub mod graph;
 pub mod resolver;
+pub mod system;
 pub mod types;
 
+pub use graph::{DependencyGraph, DependencyNode, DependencyRequester, DependencySource};
 pub use resolver::{DependencyBuildOutput, DependencyResolver};
+pub use system::SystemDependency;
+pub use types::{BuildContext, DependencyBuilder, DependencyBuilders};
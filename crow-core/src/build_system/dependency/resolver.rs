@@ -1,32 +1,159 @@
-use super::types::{cmake, crow};
+use super::graph::{DependencyGraph, DependencyNode, DependencyRequester, DependencySource};
+use super::system::SystemDependency;
+use super::types::{BuildContext, DependencyBuilder, DependencyBuilders};
 use crate::build_system;
-use crate::config::{BuildProfile, CrowDependencyBuild, Dependency, ToolchainConfig};
-use anyhow::Context;
+use crate::build_system::toolchain::flags::FlagsConverter;
+use crate::build_system::{BuildSystem, GitManager, Workspace};
+use crate::config::{
+    BuildProfile, BuildSystemType, Dependency, Language, OutputType, ToolchainConfig,
+};
+use anyhow::{anyhow, Context};
 use crow_utils::logger::{LogLevel, Logger};
-use std::collections::HashMap;
+use crow_utils::Environment;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 
 #[derive(Debug, Clone)]
 pub struct DependencyBuildOutput {
     pub lib_name: String,
-    pub library_path: PathBuf,
+    /// `None` for header-only outputs, which are not linked.
+    pub library_path: Option<PathBuf>,
+    /// Directory of the library; the package directory of a header-only output, or the
+    /// directory of the `.pc` file of a system library.
     pub library_dir: PathBuf,
     pub include_paths: Vec<String>,
+    pub defines: Vec<String>,
+    /// Other flags consumers compile with, e.g. `-pthread` from pkg-config.
+    pub compile_flags: Vec<String>,
+    pub libs: Vec<String>,
+    pub lib_dirs: Vec<PathBuf>,
+    pub link_flags: Vec<String>,
+    pub dependencies: Vec<DependencyBuildOutput>,
+}
+
+impl DependencyBuildOutput {
+    pub fn new(lib_name: String, library_path: PathBuf, include_paths: Vec<String>) -> Self {
+        let library_dir = library_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        DependencyBuildOutput {
+            lib_name,
+            library_path: Some(library_path),
+            library_dir,
+            include_paths,
+            defines: Vec::new(),
+            compile_flags: Vec::new(),
+            libs: Vec::new(),
+            lib_dirs: Vec::new(),
+            link_flags: Vec::new(),
+            dependencies: Vec::new(),
+        }
+    }
+
+    /// An output that only passes include paths and definitions to its consumers.
+    pub fn header_only(lib_name: String, package_dir: PathBuf, include_paths: Vec<String>) -> Self {
+        DependencyBuildOutput {
+            library_path: None,
+            library_dir: package_dir,
+            ..Self::new(lib_name, PathBuf::new(), include_paths)
+        }
+    }
+
+    /// Direct and transitive outputs in link order: every library comes before the
+    /// libraries it depends on, and each library appears once.
+    pub fn flatten<'a>(
+        outputs: impl IntoIterator<Item = &'a DependencyBuildOutput>,
+    ) -> Vec<DependencyBuildOutput> {
+        fn visit(
+            output: &DependencyBuildOutput,
+            seen: &mut HashSet<PathBuf>,
+            ordered: &mut Vec<DependencyBuildOutput>,
+        ) {
+            // Several system libraries can share one pkg-config directory.
+            let key = output
+                .library_path
+                .clone()
+                .unwrap_or_else(|| output.library_dir.join(&output.lib_name));
+            if !seen.insert(key) {
+                return;
+            }
+            for dep in &output.dependencies {
+                visit(dep, seen, ordered);
+            }
+            ordered.push(output.clone());
+        }
+
+        let mut seen = HashSet::new();
+        let mut ordered = Vec::new();
+        for output in outputs {
+            visit(output, &mut seen, &mut ordered);
+        }
+        ordered.reverse();
+        ordered
+    }
 }
 
 pub trait DependencyResolver {
+    #[allow(clippy::too_many_arguments)]
     fn resolve_dependencies(
-        dependencies: &HashMap<String, Dependency>,
+        root: &Path,
+        package_name: &str,
+        dependencies: &IndexMap<String, Dependency>,
         toolchain: &ToolchainConfig,
         current_profile: &str,
         profile_config: &BuildProfile,
+        jobs: Option<usize>,
         global_deps: bool,
+        offline: bool,
         logger: Logger,
     ) -> anyhow::Result<(
-        HashMap<String, PathBuf>,
-        HashMap<String, DependencyBuildOutput>,
+        IndexMap<String, PathBuf>,
+        IndexMap<String, DependencyBuildOutput>,
     )>;
 
+    fn resolve_dependency_graph(
+        root: &Path,
+        package_name: &str,
+        dependencies: &IndexMap<String, Dependency>,
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<DependencyGraph>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_dependency_graph(
+        graph: &DependencyGraph,
+        roots: &[String],
+        toolchain: &ToolchainConfig,
+        current_profile: &str,
+        profile_config: &BuildProfile,
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<IndexMap<String, DependencyBuildOutput>>;
+
+    /// The outputs of the graph that are available without building anything. Nodes that
+    /// have not been built with the current inputs are left out.
+    #[allow(clippy::too_many_arguments)]
+    fn locate_dependency_graph(
+        graph: &DependencyGraph,
+        roots: &[String],
+        toolchain: &ToolchainConfig,
+        current_profile: &str,
+        profile_config: &BuildProfile,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<IndexMap<String, DependencyBuildOutput>>;
+
     fn copy_local_dependency(
         name: &str,
         local_path_orig: &Path,
@@ -37,202 +164,238 @@ pub trait DependencyResolver {
 
 impl DependencyResolver for build_system::BuildSystem {
     fn resolve_dependencies(
-        dependencies: &HashMap<String, Dependency>,
+        root: &Path,
+        package_name: &str,
+        dependencies: &IndexMap<String, Dependency>,
         toolchain: &ToolchainConfig,
         current_profile: &str,
         profile_config: &BuildProfile,
+        jobs: Option<usize>,
         global_deps: bool,
+        offline: bool,
         logger: Logger,
     ) -> anyhow::Result<(
-        HashMap<String, PathBuf>,
-        HashMap<String, DependencyBuildOutput>,
+        IndexMap<String, PathBuf>,
+        IndexMap<String, DependencyBuildOutput>,
     )> {
         if dependencies.is_empty() {
-            return Ok((HashMap::new(), HashMap::new()));
+            return Ok((IndexMap::new(), IndexMap::new()));
         }
 
         logger.log(LogLevel::Bold, "Checking dependencies...", 1);
-        let deps_download_dir = crow_utils::environment::Environment::deps_dir(global_deps);
-        std::fs::create_dir_all(&deps_download_dir)?;
-
-        let original_cwd = std::env::current_dir()?;
-        let mut downloaded_paths = HashMap::new();
-        let mut dep_build_outputs = HashMap::new();
-
-        let has_git_deps = dependencies
-            .values()
-            .any(|dep| matches!(dep, Dependency::Git { .. }));
-        if has_git_deps {
-            <build_system::BuildSystem as build_system::GitManager>::check_git_available()?;
-        }
-
-        for (name, dep) in dependencies {
-            let dep_source_path: PathBuf;
-            let crow_build_config: CrowDependencyBuild;
-
-            match dep {
-                Dependency::Git { git, branch, build } => {
-                    let git_dep_target_path = deps_download_dir.join(name);
-
-                    if git_dep_target_path.exists() {
-                        if logger.verbose {
-                            logger.log(
-                                LogLevel::Dim,
-                                &format!("Dependency '{name}' exists. Pulling updates..."),
-                                1,
-                            );
-                        } else {
-                            logger.log(LogLevel::Info, &format!("[UPDATING] {name} ({})", git), 2);
-                        }
-                        <build_system::BuildSystem as build_system::GitManager>::git_pull(
-                            &git_dep_target_path,
-                            &logger.clone(),
-                        )?;
-                    } else {
-                        if logger.verbose {
-                            logger.log(
-                                LogLevel::Dim,
-                                &format!("Cloning new dependency '{name}' from {}", git),
-                                1,
-                            );
-                        } else {
-                            logger.log(
-                                LogLevel::Custom("\x1b[32m"),
-                                &format!("[CLONING] {name} ({})", git),
-                                2,
-                            );
-                        }
-                        <build_system::BuildSystem as build_system::GitManager>::git_clone(
-                            git,
-                            branch,
-                            &git_dep_target_path,
-                            &logger.clone(),
-                        )?;
-                    }
-                    dep_source_path = git_dep_target_path;
-                    crow_build_config =
-                        CrowDependencyBuild::infer_defaults(&dep_source_path, name, build.clone());
-                }
-                Dependency::Path { path, build } => {
-                    let local_path_orig = PathBuf::from(path);
-                    if !local_path_orig.exists() {
-                        anyhow::bail!(
-                            "Local dependency path '{}' for '{}' does not exist.",
-                            local_path_orig.display(),
-                            name
-                        );
-                    }
-
-                    if global_deps {
-                        let global_local_dep_target_path = deps_download_dir.join(name);
-                        Self::copy_local_dependency(
-                            name,
-                            &local_path_orig,
-                            &global_local_dep_target_path,
-                            logger.clone(),
-                        )?;
-                        dep_source_path = global_local_dep_target_path;
-                    } else {
-                        if logger.verbose {
-                            logger.log(
-                                LogLevel::Dim,
-                                &format!(
-                                    "Using local dependency '{name}' from {}",
-                                    local_path_orig.display()
-                                ),
-                                1,
-                            );
-                        } else {
-                            logger.log(
-                                LogLevel::Info,
-                                &format!("[LOCAL] {name} ({})", local_path_orig.display()),
-                                2,
-                            );
-                        }
-                        dep_source_path = local_path_orig;
-                    }
-                    crow_build_config =
-                        CrowDependencyBuild::infer_defaults(&dep_source_path, name, build.clone());
-                }
-            };
+        let graph = Self::resolve_dependency_graph(
+            root,
+            package_name,
+            dependencies,
+            jobs,
+            global_deps,
+            offline,
+            logger.clone(),
+        )?;
+        let roots: Vec<String> = dependencies.keys().cloned().collect();
+        let built_outputs = Self::build_dependency_graph(
+            &graph,
+            &roots,
+            toolchain,
+            current_profile,
+            profile_config,
+            jobs,
+            global_deps,
+            offline,
+            logger.clone(),
+        )?;
+        logger.log(LogLevel::Bold, "Dependencies checked.", 1);
 
-            downloaded_paths.insert(name.clone(), dep_source_path.clone());
+        let downloaded_paths = roots
+            .iter()
+            .map(|name| (name.clone(), graph.nodes[name].source_path.clone()))
+            .collect();
+        let dep_build_outputs = roots
+            .iter()
+            .map(|name| (name.clone(), built_outputs[name].clone()))
+            .collect();
+        Ok((downloaded_paths, dep_build_outputs))
+    }
 
-            let build_output_dir = dep_source_path.join("_crow_build").join(current_profile);
-            let lib_name_str = &crow_build_config.lib_name;
+    fn resolve_dependency_graph(
+        root: &Path,
+        package_name: &str,
+        dependencies: &IndexMap<String, Dependency>,
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<DependencyGraph> {
+        match Workspace::discover(root)? {
+            Some(workspace) => DependencyGraph::collect(
+                &workspace.root,
+                &workspace.requesters(),
+                jobs,
+                global_deps,
+                offline,
+                logger,
+            ),
+            None => DependencyGraph::collect(
+                root,
+                &[DependencyRequester {
+                    name: package_name.to_string(),
+                    root: root.to_path_buf(),
+                    dependencies,
+                }],
+                jobs,
+                global_deps,
+                offline,
+                logger,
+            ),
+        }
+    }
 
-            let expected_lib_path = <build_system::builder::BuildSystem as build_system::ToolchainExecutor>::find_library_file(
-                &build_output_dir,
-                lib_name_str,
-                &crow_build_config.output_type,
-            );
+    fn build_dependency_graph(
+        graph: &DependencyGraph,
+        roots: &[String],
+        toolchain: &ToolchainConfig,
+        current_profile: &str,
+        profile_config: &BuildProfile,
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<IndexMap<String, DependencyBuildOutput>> {
+        let mut pending = graph.build_order(roots)?;
+        let order: Vec<String> = pending.iter().map(|node| node.name.clone()).collect();
+        let num_jobs = jobs.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
 
-            if let Some(lib_path) = expected_lib_path {
-                logger.log(
-                    LogLevel::Info,
-                    &format!("[CACHED] Dependency '{name}' (profile: {current_profile})."),
-                    2,
-                );
-                if logger.verbose {
-                    logger.log(
-                        LogLevel::Dim,
-                        &format!("Library found at: {}", lib_path.display()),
-                        2,
-                    );
+        let mut built_outputs: IndexMap<String, DependencyBuildOutput> = IndexMap::new();
+        let stamps = Stamps::default();
+        let mut first_error = None;
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|scope| -> anyhow::Result<()> {
+            let mut running = 0;
+            loop {
+                // Start every dependency whose own dependencies are built, up to `num_jobs`
+                // at a time. After a failure, only the running builds are waited for.
+                let mut ready = Vec::new();
+                while first_error.is_none() && running + ready.len() < num_jobs {
+                    let Some(index) = pending.iter().position(|node| {
+                        node.dependencies
+                            .iter()
+                            .all(|dep| built_outputs.contains_key(dep))
+                    }) else {
+                        break;
+                    };
+                    ready.push(pending.remove(index));
                 }
 
-                let mut include_paths = vec![".".to_string()];
-                if dep_source_path.join("include").exists() {
-                    include_paths.push("include".to_string());
+                // The dependencies built at the same time share the jobs, so that at most
+                // about `num_jobs` compilers run at once.
+                let node_jobs = Some((num_jobs / (running + ready.len()).max(1)).max(1));
+                for node in ready {
+                    let dependency_outputs = node
+                        .dependencies
+                        .iter()
+                        .map(|dep| (dep.clone(), built_outputs[dep].clone()))
+                        .collect();
+                    let tx = tx.clone();
+                    let logger = logger.clone();
+                    let stamps = &stamps;
+                    running += 1;
+                    scope.spawn(move || {
+                        // A panic becomes an error; otherwise no result would be sent and
+                        // the wait below would never end.
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                            build_node(
+                                graph,
+                                node,
+                                dependency_outputs,
+                                toolchain,
+                                current_profile,
+                                profile_config,
+                                node_jobs,
+                                global_deps,
+                                offline,
+                                stamps,
+                                logger,
+                            )
+                        }))
+                        .unwrap_or_else(|payload| {
+                            let message = payload
+                                .downcast_ref::<&str>()
+                                .map(|m| m.to_string())
+                                .or_else(|| payload.downcast_ref::<String>().cloned())
+                                .unwrap_or_default();
+                            Err(anyhow!(
+                                "Building dependency '{}' panicked: {message}",
+                                node.name
+                            ))
+                        });
+                        let _ = tx.send((node.name.clone(), result));
+                    });
+                }
+                if running == 0 {
+                    return Ok(());
                 }
 
-                let output = DependencyBuildOutput {
-                    lib_name: lib_name_str.to_string(),
-                    library_path: lib_path.clone(),
-                    library_dir: lib_path.parent().unwrap().to_path_buf(),
-                    include_paths,
-                };
-                dep_build_outputs.insert(name.clone(), output);
-                continue;
+                let (name, result) = rx.recv()?;
+                running -= 1;
+                match result {
+                    Ok(output) => {
+                        built_outputs.insert(name, output);
+                    }
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                    }
+                }
             }
+        })?;
+        if let Some(e) = first_error {
+            return Err(e);
+        }
 
-            logger.log(
-                LogLevel::Bold,
-                &format!("Building dependency '{name}'..."),
-                1,
-            );
-            std::env::set_current_dir(&dep_source_path)?;
+        // Builds finish in any order; keep the outputs in build order.
+        Ok(order
+            .into_iter()
+            .filter_map(|name| built_outputs.swap_remove_entry(&name))
+            .collect())
+    }
 
-            let build_output = match crow_build_config.build_system {
-                Some(crate::config::BuildSystemType::Cmake) => cmake::CmakeDependency::build(
-                    name,
-                    toolchain,
-                    &crow_build_config,
-                    current_profile,
-                    profile_config,
-                    logger.clone(),
-                ),
-                Some(crate::config::BuildSystemType::Crow) => crow::CrowDependency::build(
-                    name,
-                    &dep_source_path,
-                    &crow_build_config,
-                    current_profile,
-                    global_deps,
-                    logger.clone(),
-                ),
-                None => anyhow::bail!("Build system for dependency '{}' was not inferred.", name),
-            }?;
-
-            dep_build_outputs.insert(name.clone(), build_output);
-            std::env::set_current_dir(&original_cwd)?;
-            logger.log(
-                LogLevel::Bold,
-                &format!("Finished building dependency '{name}'."),
-                1,
-            );
+    fn locate_dependency_graph(
+        graph: &DependencyGraph,
+        roots: &[String],
+        toolchain: &ToolchainConfig,
+        current_profile: &str,
+        profile_config: &BuildProfile,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<IndexMap<String, DependencyBuildOutput>> {
+        let mut located = IndexMap::new();
+        let stamps = Stamps::default();
+        for node in graph.build_order(roots)? {
+            let dependency_outputs = node
+                .dependencies
+                .iter()
+                .filter_map(|dep| Some((dep.clone(), located.get(dep).cloned()?)))
+                .collect();
+            if let Some(output) = locate_node(
+                graph,
+                node,
+                dependency_outputs,
+                toolchain,
+                current_profile,
+                profile_config,
+                global_deps,
+                offline,
+                &stamps,
+                logger.clone(),
+            )? {
+                located.insert(node.name.clone(), output);
+            }
         }
-        logger.log(LogLevel::Bold, "Dependencies checked.", 1);
-        Ok((downloaded_paths, dep_build_outputs))
+        Ok(located)
     }
 
     fn copy_local_dependency(
@@ -295,3 +458,524 @@ impl DependencyResolver for build_system::BuildSystem {
         Ok(())
     }
 }
+
+/// Builds one dependency of the graph, or reuses its previous build if the build inputs
+/// are unchanged. `dependency_outputs` holds the built dependencies of the node.
+#[allow(clippy::too_many_arguments)]
+fn build_node(
+    graph: &DependencyGraph,
+    node: &DependencyNode,
+    dependency_outputs: IndexMap<String, DependencyBuildOutput>,
+    toolchain: &ToolchainConfig,
+    current_profile: &str,
+    profile_config: &BuildProfile,
+    jobs: Option<usize>,
+    global_deps: bool,
+    offline: bool,
+    stamps: &Stamps,
+    logger: Logger,
+) -> anyhow::Result<DependencyBuildOutput> {
+    let name = &node.name;
+    let dep_source_path = &node.source_path;
+
+    if let Some(output) = unbuilt_output(node, &logger) {
+        return output;
+    }
+    let builder = node_builder(node)?;
+    let ctx = node_context(
+        graph,
+        node,
+        dependency_outputs,
+        toolchain,
+        current_profile,
+        profile_config,
+        jobs,
+        global_deps,
+        offline,
+        logger.clone(),
+    );
+    let build_output_dir = ctx.build_dir();
+
+    // A build is reused only if it was made from the same inputs.
+    let stamp = build_stamp(
+        graph,
+        node,
+        toolchain,
+        current_profile,
+        profile_config,
+        stamps,
+        &logger,
+    )?;
+    let stamp_path = build_output_dir.join(BUILD_STAMP_FILE);
+    let stamp_matches = std::fs::read_to_string(&stamp_path).is_ok_and(|old| old.trim() == stamp);
+    if build_output_dir.exists() && !stamp_matches {
+        logger.log(
+            LogLevel::Info,
+            format!("[REBUILD] Dependency '{name}': build inputs changed."),
+            2,
+        );
+        std::fs::remove_dir_all(&build_output_dir)?;
+    }
+
+    // An unreadable previous build is simply built again.
+    let cached_output = if stamp_matches {
+        builder.locate_outputs(&ctx).ok().flatten()
+    } else {
+        None
+    };
+
+    if let Some(mut output) = cached_output {
+        logger.log(
+            LogLevel::Info,
+            &format!("[CACHED] Dependency '{name}' (profile: {current_profile})."),
+            2,
+        );
+        if let Some(library_path) = output.library_path.as_ref().filter(|_| logger.verbose) {
+            logger.log(
+                LogLevel::Dim,
+                &format!("Library found at: {}", library_path.display()),
+                2,
+            );
+        }
+
+        absolutize_include_paths(&mut output, dep_source_path);
+        return Ok(output);
+    }
+
+    logger.log(
+        LogLevel::Bold,
+        &format!("Building dependency '{name}'..."),
+        1,
+    );
+
+    builder.configure(&ctx)?;
+    let mut build_output = builder.build(&ctx)?;
+
+    absolutize_include_paths(&mut build_output, dep_source_path);
+    if build_output_dir.exists() {
+        std::fs::write(&stamp_path, &stamp)?;
+    }
+    logger.log(
+        LogLevel::Bold,
+        &format!("Finished building dependency '{name}'."),
+        1,
+    );
+    Ok(build_output)
+}
+
+/// The outputs of dependencies that are never built: system libraries, whose flags come
+/// from pkg-config, and header-only dependencies without a crow.toml, which are used as
+/// they are checked out.
+fn unbuilt_output(
+    node: &DependencyNode,
+    logger: &Logger,
+) -> Option<anyhow::Result<DependencyBuildOutput>> {
+    let name = &node.name;
+    let dep_source_path = &node.source_path;
+    let crow_build_config = &node.build_config;
+
+    if let DependencySource::System { package, version } = &node.source {
+        logger.log(
+            LogLevel::Info,
+            format!("[SYSTEM] Dependency '{name}' ({package})."),
+            2,
+        );
+        let version = Some(version.as_str()).filter(|v| !v.is_empty());
+        return Some(
+            SystemDependency::new(package, version)
+                .output(&crow_build_config.lib_name, dep_source_path.clone()),
+        );
+    }
+
+    if crow_build_config.output_type != OutputType::HeaderOnly
+        || crow_build_config.build_system == Some(BuildSystemType::Crow)
+    {
+        return None;
+    }
+    logger.log(
+        LogLevel::Info,
+        format!("[HEADER-ONLY] Dependency '{name}'."),
+        2,
+    );
+    let include_paths = if crow_build_config.includes.is_empty() {
+        let mut include_paths = vec![".".to_string()];
+        if dep_source_path.join("include").exists() {
+            include_paths.push("include".to_string());
+        }
+        include_paths
+    } else {
+        crow_build_config.includes.clone()
+    };
+    let mut output = DependencyBuildOutput::header_only(
+        crow_build_config.lib_name.clone(),
+        dep_source_path.clone(),
+        include_paths,
+    );
+    output.defines = crow_build_config.defines.clone();
+    absolutize_include_paths(&mut output, dep_source_path);
+    Some(Ok(output))
+}
+
+fn node_builder(node: &DependencyNode) -> anyhow::Result<Arc<dyn DependencyBuilder>> {
+    let name = &node.name;
+    let build_system = node
+        .build_config
+        .build_system
+        .as_ref()
+        .ok_or_else(|| anyhow!("Build system for dependency '{}' was not inferred.", name))?;
+    DependencyBuilders::get(&build_system.to_string()).ok_or_else(|| {
+        anyhow!(
+            "Dependency '{}' uses build system '{}', but no builder for it is registered. Known build systems: {}",
+            name,
+            build_system,
+            DependencyBuilders::names().join(", ")
+        )
+    })
+}
+
+/// The dependencies of a crow package come from the same graph, so a library shared
+/// by several packages is built and linked once.
+#[allow(clippy::too_many_arguments)]
+fn node_context<'a>(
+    graph: &DependencyGraph,
+    node: &'a DependencyNode,
+    dependency_outputs: IndexMap<String, DependencyBuildOutput>,
+    toolchain: &'a ToolchainConfig,
+    current_profile: &'a str,
+    profile_config: &'a BuildProfile,
+    jobs: Option<usize>,
+    global_deps: bool,
+    offline: bool,
+    logger: Logger,
+) -> BuildContext<'a> {
+    BuildContext {
+        name: &node.name,
+        source_dir: &node.source_path,
+        config: &node.build_config,
+        toolchain,
+        profile: current_profile,
+        profile_config,
+        jobs,
+        dependency_paths: node
+            .dependencies
+            .iter()
+            .map(|dep| (dep.clone(), graph.nodes[dep].source_path.clone()))
+            .collect(),
+        dependency_outputs,
+        global_deps,
+        offline,
+        logger,
+    }
+}
+
+/// The output of a dependency that needs no build or was built before from the same
+/// inputs, or `None`. Nothing is built or removed.
+#[allow(clippy::too_many_arguments)]
+fn locate_node(
+    graph: &DependencyGraph,
+    node: &DependencyNode,
+    dependency_outputs: IndexMap<String, DependencyBuildOutput>,
+    toolchain: &ToolchainConfig,
+    current_profile: &str,
+    profile_config: &BuildProfile,
+    global_deps: bool,
+    offline: bool,
+    stamps: &Stamps,
+    logger: Logger,
+) -> anyhow::Result<Option<DependencyBuildOutput>> {
+    if let Some(output) = unbuilt_output(node, &logger) {
+        return output.map(Some);
+    }
+    let builder = node_builder(node)?;
+    let ctx = node_context(
+        graph,
+        node,
+        dependency_outputs,
+        toolchain,
+        current_profile,
+        profile_config,
+        None,
+        global_deps,
+        offline,
+        logger.clone(),
+    );
+
+    let stamp = build_stamp(
+        graph,
+        node,
+        toolchain,
+        current_profile,
+        profile_config,
+        stamps,
+        &logger,
+    )?;
+    let stamp_path = ctx.build_dir().join(BUILD_STAMP_FILE);
+    if !std::fs::read_to_string(&stamp_path).is_ok_and(|old| old.trim() == stamp) {
+        return Ok(None);
+    }
+    Ok(builder
+        .locate_outputs(&ctx)
+        .ok()
+        .flatten()
+        .map(|mut output| {
+            absolutize_include_paths(&mut output, &node.source_path);
+            output
+        }))
+}
+
+fn absolutize_include_paths(output: &mut DependencyBuildOutput, dep_root: &Path) {
+    output.include_paths = output
+        .include_paths
+        .iter()
+        .map(|include| dep_root.join(include).to_string_lossy().to_string())
+        .collect();
+}
+
+const BUILD_STAMP_FILE: &str = ".crow-stamp";
+
+/// Build stamps by dependency name, computed once per build of a graph.
+type Stamps = Mutex<HashMap<String, String>>;
+
+/// Hash of everything a dependency build depends on: the checked out revision or, for a
+/// path dependency, the contents of its sources; the dependency's build options, the
+/// toolchain and compilers, the profile, and the stamps of its own dependencies.
+fn build_stamp(
+    graph: &DependencyGraph,
+    node: &DependencyNode,
+    toolchain: &ToolchainConfig,
+    profile: &str,
+    profile_config: &BuildProfile,
+    stamps: &Stamps,
+    logger: &Logger,
+) -> anyhow::Result<String> {
+    if let Some(stamp) = stamps.lock().unwrap().get(&node.name) {
+        return Ok(stamp.clone());
+    }
+    let mut hasher = xxhash_rust::xxh3::Xxh3::default();
+    match node.source {
+        DependencySource::Git { .. } => {
+            let revision = BuildSystem::git_head_rev(&node.source_path).unwrap_or_default();
+            hasher.update(revision.as_bytes());
+        }
+        DependencySource::Path(_) => hash_sources(&node.source_path, &mut hasher)?,
+        DependencySource::System { .. } => {}
+    }
+    hasher.update(serde_json::to_string(&node.build_config)?.as_bytes());
+    hasher.update(serde_json::to_string(toolchain)?.as_bytes());
+    for language in [Language::C, Language::Cxx] {
+        if let Some(compiler) = BuildSystem::resolve_compiler(&toolchain.compiler_for(language)) {
+            let fingerprint = BuildSystem::compiler_info(&compiler, logger).fingerprint;
+            hasher.update(&fingerprint.to_le_bytes());
+        }
+    }
+    hasher.update(profile.as_bytes());
+    hasher.update(serde_json::to_string(profile_config)?.as_bytes());
+    // A dependency that is built again makes the packages built on it build again too.
+    for child in &node.dependencies {
+        let child_stamp = build_stamp(
+            graph,
+            &graph.nodes[child],
+            toolchain,
+            profile,
+            profile_config,
+            stamps,
+            logger,
+        )?;
+        hasher.update(child_stamp.as_bytes());
+    }
+    let stamp = format!("{:016x}", hasher.digest());
+    stamps
+        .lock()
+        .unwrap()
+        .insert(node.name.clone(), stamp.clone());
+    Ok(stamp)
+}
+
+/// Hashes the relative path and contents of every file under `dir`, leaving out build
+/// directories, fetched dependencies and `.git`.
+fn hash_sources(dir: &Path, hasher: &mut xxhash_rust::xxh3::Xxh3) -> anyhow::Result<()> {
+    fn visit(
+        root: &Path,
+        dir: &Path,
+        skipped: &[&OsStr],
+        hasher: &mut xxhash_rust::xxh3::Xxh3,
+    ) -> anyhow::Result<()> {
+        let mut entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                if !skipped.contains(&entry.file_name().as_os_str()) {
+                    visit(root, &path, skipped, hasher)?;
+                }
+            } else {
+                hasher.update(path.strip_prefix(root)?.to_string_lossy().as_bytes());
+                hasher.update(&xxhash_rust::xxh3::xxh3_64(&std::fs::read(&path)?).to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    let build_dir = Environment::build_dir();
+    let mut skipped = vec![
+        OsStr::new("_crow_build"),
+        OsStr::new(".crow"),
+        OsStr::new(".git"),
+    ];
+    skipped.extend(build_dir.file_name());
+    visit(dir, dir, &skipped, hasher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CrowDependencyBuild;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn build_dependencies(app: &Path) -> IndexMap<String, DependencyBuildOutput> {
+        let config = crate::config::Config::load(app.join("crow.toml")).unwrap();
+        BuildSystem::new(config, app, "debug", Some(1), false, false, Logger::new())
+            .unwrap()
+            .dep_build_outputs
+    }
+
+    #[test]
+    fn unchanged_crow_dependency_is_not_built_again() {
+        let root = std::env::temp_dir().join(format!("crow-resolver-cache-{}", std::process::id()));
+        let app = root.join("app");
+        write(
+            &app.join("crow.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\nutil = { path = \"../util\" }\n",
+        );
+        write(
+            &root.join("util/crow.toml"),
+            "[package]\nname = \"util\"\noutput_type = \"static-lib\"\n\n[package.exports]\ndefines = [\"UTIL\"]\n",
+        );
+        write(
+            &root.join("util/src/util.cpp"),
+            "int util() { return 42; }\n",
+        );
+
+        let first = build_dependencies(&app);
+        let library_path = first["util"].library_path.clone().unwrap();
+        assert!(library_path.starts_with(root.join("util/_crow_build/debug")));
+        assert!(root
+            .join("util/_crow_build/debug")
+            .join(BUILD_STAMP_FILE)
+            .exists());
+
+        // A second build would replace the library; the cached one is kept as it is.
+        std::fs::write(&library_path, "cached").unwrap();
+        let second = build_dependencies(&app);
+        assert_eq!(std::fs::read_to_string(&library_path).unwrap(), "cached");
+        assert_eq!(second["util"].library_path, first["util"].library_path);
+        assert_eq!(second["util"].defines, vec!["UTIL".to_string()]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    struct Panicking;
+
+    impl DependencyBuilder for Panicking {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        fn detect(&self, source_dir: &Path) -> bool {
+            source_dir.join("PANICS").exists()
+        }
+
+        fn build(&self, _ctx: &BuildContext) -> anyhow::Result<DependencyBuildOutput> {
+            panic!("builder bug")
+        }
+
+        fn locate_outputs(
+            &self,
+            _ctx: &BuildContext,
+        ) -> anyhow::Result<Option<DependencyBuildOutput>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn panicking_builder_fails_the_build() {
+        DependencyBuilders::register(Panicking);
+        let root = std::env::temp_dir().join(format!("crow-resolver-panic-{}", std::process::id()));
+        let app = root.join("app");
+        write(
+            &app.join("crow.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\nbad = { path = \"../bad\" }\n",
+        );
+        write(&root.join("bad/PANICS"), "");
+
+        let config = crate::config::Config::load(app.join("crow.toml")).unwrap();
+        let error = BuildSystem::new(config, &app, "debug", Some(2), false, false, Logger::new())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Building dependency 'bad' panicked: builder bug"
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn build_stamps_are_computed_once_per_node() {
+        let mut graph = DependencyGraph::default();
+        for (name, dependencies) in [
+            ("app", vec!["left", "right"]),
+            ("left", vec!["base"]),
+            ("right", vec!["base"]),
+            ("base", vec![]),
+        ] {
+            graph.nodes.insert(
+                name.to_string(),
+                DependencyNode {
+                    name: name.to_string(),
+                    dependency: Dependency::System {
+                        system: name.to_string(),
+                        version: None,
+                    },
+                    source: DependencySource::System {
+                        package: name.to_string(),
+                        version: String::new(),
+                    },
+                    source_path: PathBuf::new(),
+                    build_config: CrowDependencyBuild::default(),
+                    requested_by: Vec::new(),
+                    dependencies: dependencies.into_iter().map(String::from).collect(),
+                },
+            );
+        }
+        let stamp = |stamps: &Stamps| {
+            build_stamp(
+                &graph,
+                &graph.nodes["app"],
+                &ToolchainConfig::default(),
+                "debug",
+                &BuildProfile::default(),
+                stamps,
+                &Logger::new(),
+            )
+            .unwrap()
+        };
+
+        let stamps = Stamps::default();
+        let first = stamp(&stamps);
+        assert_eq!(stamps.lock().unwrap().len(), 4);
+        assert_eq!(stamp(&stamps), first);
+
+        // A stamp already known for the shared `base` is used as it is.
+        let known = Stamps::default();
+        known
+            .lock()
+            .unwrap()
+            .insert("base".to_string(), "changed".to_string());
+        assert_ne!(stamp(&known), first);
+    }
+}
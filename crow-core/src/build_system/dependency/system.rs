@@ -0,0 +1,166 @@
+//! Libraries installed on the system, found through pkg-config.
+
+use crate::DependencyBuildOutput;
+use anyhow::Context;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+pub struct SystemDependency<'a> {
+    pub package: &'a str,
+    pub version: Option<&'a str>,
+}
+
+impl<'a> SystemDependency<'a> {
+    pub fn new(package: &'a str, version: Option<&'a str>) -> Self {
+        SystemDependency { package, version }
+    }
+
+    /// Checks that the package is installed in a version that satisfies the requirement,
+    /// and returns the directory of its `.pc` file.
+    pub fn probe(&self, name: &str) -> anyhow::Result<PathBuf> {
+        let package = self.package;
+        if !pkg_config(&["--exists", package])?.status.success() {
+            anyhow::bail!(
+                "System library '{package}' for dependency '{name}' was not found by pkg-config.\n\
+                 Install its development package (often named `{package}-dev` or `{package}-devel`), \
+                 or add the directory containing `{package}.pc` to PKG_CONFIG_PATH."
+            );
+        }
+
+        if let Some(version) = self.version {
+            let requirement = requirement(package, version)?;
+            if !pkg_config(&["--exists", &requirement])?.status.success() {
+                anyhow::bail!(
+                    "Dependency '{name}' requires {package} {version}, but version {} is installed.",
+                    self.query("--modversion")?
+                );
+            }
+        }
+        Ok(PathBuf::from(self.query("--variable=pcfiledir")?))
+    }
+
+    /// The compile and link flags of the package. `pc_dir` is the directory `probe`
+    /// returned; it identifies the output, as the package has no library file of its own.
+    pub fn output(&self, lib_name: &str, pc_dir: PathBuf) -> anyhow::Result<DependencyBuildOutput> {
+        let strip = |flags: String, prefix: &str| -> Vec<String> {
+            flags
+                .split_whitespace()
+                .filter_map(|flag| flag.strip_prefix(prefix))
+                .map(str::to_string)
+                .collect()
+        };
+
+        // `-D` flags become definitions; any other cflag is passed on as it is.
+        let (defines, compile_flags): (Vec<String>, Vec<String>) = self
+            .query("--cflags-only-other")?
+            .split_whitespace()
+            .map(str::to_string)
+            .partition(|flag| flag.starts_with("-D"));
+
+        Ok(DependencyBuildOutput {
+            lib_name: lib_name.to_string(),
+            library_path: None,
+            library_dir: pc_dir,
+            include_paths: strip(self.query("--cflags-only-I")?, "-I"),
+            defines: defines
+                .iter()
+                .map(|define| define["-D".len()..].to_string())
+                .collect(),
+            compile_flags,
+            libs: strip(self.query("--libs-only-l")?, "-l"),
+            lib_dirs: strip(self.query("--libs-only-L")?, "-L")
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+            link_flags: self
+                .query("--libs-only-other")?
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            dependencies: Vec::new(),
+        })
+    }
+
+    fn query(&self, option: &str) -> anyhow::Result<String> {
+        let output = pkg_config(&[option, self.package])?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "pkg-config {option} {} failed: {}",
+                self.package,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+/// Runs `$PKG_CONFIG`, or `pkg-config` if it is not set.
+fn pkg_config(args: &[&str]) -> anyhow::Result<Output> {
+    let program = std::env::var("PKG_CONFIG").unwrap_or_else(|_| "pkg-config".to_string());
+    Command::new(&program).args(args).output().with_context(|| {
+        format!(
+            "Failed to run `{program}`. System dependencies need pkg-config (or pkgconf); install it or set PKG_CONFIG to its path."
+        )
+    })
+}
+
+/// Turns a requirement such as `1.6` or `>=1.6, <2` into pkg-config's form,
+/// `libpng >= 1.6, libpng < 2`. A bare version means at least that version.
+fn requirement(package: &str, version: &str) -> anyhow::Result<String> {
+    let mut constraints = Vec::new();
+    for constraint in version.split(',') {
+        let constraint = constraint.trim();
+        let (operator, number) = ["==", ">=", "<=", "!=", "=", ">", "<"]
+            .iter()
+            .find_map(|operator| {
+                constraint
+                    .strip_prefix(operator)
+                    .map(|number| (*operator, number.trim()))
+            })
+            .unwrap_or((">=", constraint));
+        if number.is_empty() || number.contains(char::is_whitespace) {
+            anyhow::bail!(
+                "Invalid version requirement '{version}' for system library '{package}'."
+            );
+        }
+        let operator = if operator == "==" { "=" } else { operator };
+        constraints.push(format!("{package} {operator} {number}"));
+    }
+    Ok(constraints.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_version_means_at_least() {
+        assert_eq!(requirement("zlib", "1.2").unwrap(), "zlib >= 1.2");
+    }
+
+    #[test]
+    fn operators_are_kept_and_double_equals_becomes_equals() {
+        assert_eq!(requirement("zlib", "==1.2.13").unwrap(), "zlib = 1.2.13");
+        assert_eq!(requirement("zlib", "< 2").unwrap(), "zlib < 2");
+        assert_eq!(requirement("zlib", "!=1.3").unwrap(), "zlib != 1.3");
+    }
+
+    #[test]
+    fn constraints_are_repeated_per_package() {
+        assert_eq!(
+            requirement("libpng", ">=1.6, <2").unwrap(),
+            "libpng >= 1.6, libpng < 2"
+        );
+    }
+
+    #[test]
+    fn invalid_requirements_are_rejected() {
+        for version in ["", ">=", ">=1.6,", "1.6 2"] {
+            let error = requirement("libpng", version).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("Invalid version requirement '{version}' for system library 'libpng'.")
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use super::{external, BuildContext, DependencyBuilder};
+use crate::config::OutputType;
+use crate::DependencyBuildOutput;
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct AutotoolsDependency;
+
+/// Prefix the project is configured for; `make install` stages it under `DESTDIR`.
+const PREFIX: &str = "/usr";
+
+impl DependencyBuilder for AutotoolsDependency {
+    fn name(&self) -> &str {
+        "autotools"
+    }
+
+    fn detect(&self, source_dir: &Path) -> bool {
+        source_dir.join("configure").exists()
+    }
+
+    fn configure(&self, ctx: &BuildContext) -> anyhow::Result<()> {
+        let build_dir = ctx.build_dir();
+        std::fs::create_dir_all(&build_dir)?;
+
+        // Out-of-tree build, so every profile has its own objects.
+        if build_dir.join("Makefile").exists() {
+            return Ok(());
+        }
+        let (enable, disable) = match ctx.config.output_type {
+            OutputType::SharedLib => ("--enable-shared", "--disable-static"),
+            _ => ("--enable-static", "--disable-shared"),
+        };
+        let mut configure = Command::new(std::path::absolute(ctx.source_dir.join("configure"))?);
+        configure
+            .current_dir(&build_dir)
+            .arg(format!("--prefix={PREFIX}"))
+            .arg(format!("--libdir={PREFIX}/lib"))
+            .arg(format!("--includedir={PREFIX}/include"))
+            .arg(enable)
+            .arg(disable)
+            .envs(external::toolchain_env(
+                ctx.toolchain,
+                ctx.profile_config,
+                &ctx.logger,
+            ));
+        external::run_step(ctx.name, "configure", &mut configure, &ctx.logger)
+    }
+
+    fn build(&self, ctx: &BuildContext) -> anyhow::Result<DependencyBuildOutput> {
+        let build_dir = ctx.build_dir();
+        let destdir = std::path::absolute(build_dir.join("install"))?;
+        let env = external::toolchain_env(ctx.toolchain, ctx.profile_config, &ctx.logger);
+
+        let mut make = Command::new("make");
+        make.current_dir(&build_dir)
+            .arg(format!("-j{}", external::num_jobs(ctx.jobs)))
+            .envs(env.iter().cloned());
+        external::run_step(ctx.name, "make", &mut make, &ctx.logger)?;
+
+        let mut install = Command::new("make");
+        install
+            .current_dir(&build_dir)
+            .arg("install")
+            .arg(format!("DESTDIR={}", destdir.display()))
+            .envs(env);
+        external::run_step(ctx.name, "make install", &mut install, &ctx.logger)?;
+
+        external::installed_output(&Self::staged_prefix(ctx), ctx.config).ok_or_else(|| {
+            anyhow!(
+                "Could not find library for '{}' after install in {}",
+                ctx.name,
+                destdir.display()
+            )
+        })
+    }
+
+    fn locate_outputs(&self, ctx: &BuildContext) -> anyhow::Result<Option<DependencyBuildOutput>> {
+        Ok(external::installed_output(
+            &Self::staged_prefix(ctx),
+            ctx.config,
+        ))
+    }
+}
+
+impl AutotoolsDependency {
+    fn staged_prefix(ctx: &BuildContext) -> PathBuf {
+        ctx.build_dir()
+            .join("install")
+            .join(PREFIX.trim_start_matches('/'))
+    }
+}
@@ -1,84 +1,350 @@
-use crate::config::{BuildProfile, CrowDependencyBuild, ToolchainConfig};
+use super::cmake_api::Codemodel;
+use super::{external, BuildContext, DependencyBuilder};
+use crate::build_system::toolchain::flags::{CompilerFlavor, FlagsConverter};
+use crate::config::{BuildProfile, CrowDependencyBuild, Language, ToolchainConfig};
 use crate::{build_system, DependencyBuildOutput};
 use anyhow::anyhow;
 use crow_utils::logger::Logger;
+use std::path::{Path, PathBuf};
 
 pub struct CmakeDependency;
 
-impl CmakeDependency {
-    pub fn build(
-        name: &str,
-        toolchain: &ToolchainConfig,
-        config: &CrowDependencyBuild,
-        profile: &str,
-        profile_config: &BuildProfile,
-        logger: Logger,
-    ) -> anyhow::Result<DependencyBuildOutput> {
-        let dep_source_dir = std::env::current_dir()?;
-        let build_dir = dep_source_dir.join("_crow_build").join(profile);
-        let lib_name = config.lib_name.clone();
+const TOOLCHAIN_FILE: &str = "crow-toolchain.cmake";
 
-        std::fs::create_dir_all(&build_dir)?;
+impl DependencyBuilder for CmakeDependency {
+    fn name(&self) -> &str {
+        "cmake"
+    }
 
-        let build_type = if profile == "release" {
-            "Release"
-        } else {
-            "Debug"
-        };
+    fn detect(&self, source_dir: &Path) -> bool {
+        source_dir.join("CMakeLists.txt").exists()
+    }
 
-        let mut cxx_flags = vec![format!("-O{}", profile_config.opt_level)];
-        if profile_config.lto {
-            cxx_flags.push("-flto".to_string());
-        }
+    fn configure(&self, ctx: &BuildContext) -> anyhow::Result<()> {
+        let build_dir = ctx.build_dir();
+        std::fs::create_dir_all(&build_dir)?;
 
+        let mut pch_flags = Vec::new();
         build_system::BuildSystem::handle_pch_generation(
-            name,
-            toolchain,
-            config,
-            profile_config,
+            ctx.name,
+            ctx.toolchain,
+            ctx.config,
+            ctx.profile_config,
+            &build_dir,
+            &mut pch_flags,
+            ctx.logger.clone(),
+        )?;
+
+        let toolchain_file = Self::write_toolchain_file(
+            ctx.toolchain,
+            ctx.profile_config,
             &build_dir,
-            &mut cxx_flags,
-            logger.clone(),
+            &pch_flags,
+            &ctx.logger,
         )?;
 
-        let cxx_flags_str = cxx_flags.join(" ");
+        // A build directory configured without the File API query is configured again.
+        Codemodel::write_query(&build_dir)?;
+        let cmake_cache = build_dir.join("CMakeCache.txt");
+        if cmake_cache.exists() && !Codemodel::has_reply(&build_dir) {
+            std::fs::remove_file(&cmake_cache)?;
+        }
 
         build_system::BuildSystem::run_cmake_configure(
-            name,
-            &dep_source_dir,
+            ctx.name,
+            ctx.source_dir,
+            &build_dir,
+            Self::build_type(ctx.profile),
+            &toolchain_file,
+            &ctx.config.cmake_options,
+            ctx.logger.clone(),
+        )
+    }
+
+    fn build(&self, ctx: &BuildContext) -> anyhow::Result<DependencyBuildOutput> {
+        let build_dir = ctx.build_dir();
+        let build_type = Self::build_type(ctx.profile);
+        let codemodel = Codemodel::read(&build_dir, build_type)?;
+        let targets = Self::select_targets(ctx.name, codemodel.as_ref(), ctx.config)?;
+        build_system::BuildSystem::run_cmake_build(
+            ctx.name,
             &build_dir,
             build_type,
-            toolchain,
-            &cxx_flags_str,
-            &config.cmake_options,
-            logger.clone(),
+            &targets,
+            ctx.logger.clone(),
         )?;
-        build_system::BuildSystem::run_cmake_build(name, &build_dir, build_type, logger.clone())?;
 
-        let library_path =
-            <build_system::builder::BuildSystem as build_system::ToolchainExecutor>::find_library_file(
-                &build_dir,
-                &lib_name,
-                &config.output_type,
+        Self::output(
+            ctx.source_dir,
+            &build_dir,
+            codemodel.as_ref(),
+            &targets,
+            ctx.config,
+        )
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not find library for '{}' after build in {}",
+                ctx.name,
+                build_dir.display()
             )
-            .ok_or_else(|| {
-                anyhow!(
-                    "Could not find library for '{}' after build in {}",
-                    name,
-                    build_dir.display()
+        })
+    }
+
+    fn locate_outputs(&self, ctx: &BuildContext) -> anyhow::Result<Option<DependencyBuildOutput>> {
+        let build_dir = ctx.build_dir();
+        let codemodel = Codemodel::read(&build_dir, Self::build_type(ctx.profile))?;
+        let targets = Self::select_targets(ctx.name, codemodel.as_ref(), ctx.config)?;
+        Ok(Self::output(
+            ctx.source_dir,
+            &build_dir,
+            codemodel.as_ref(),
+            &targets,
+            ctx.config,
+        ))
+    }
+}
+
+impl CmakeDependency {
+    fn build_type(profile: &str) -> &'static str {
+        if profile == "release" {
+            "Release"
+        } else {
+            "Debug"
+        }
+    }
+
+    /// The CMake targets to build and link: the configured `targets`, or else the library
+    /// target named like `lib_name`. Empty if CMake wrote no File API reply or has no such
+    /// target, in which case everything is built and the library is searched for.
+    fn select_targets(
+        name: &str,
+        codemodel: Option<&Codemodel>,
+        config: &CrowDependencyBuild,
+    ) -> anyhow::Result<Vec<String>> {
+        let Some(codemodel) = codemodel else {
+            if !config.targets.is_empty() {
+                anyhow::bail!(
+                    "Dependency '{name}' selects CMake targets, but CMake wrote no File API reply (CMake 3.14 or newer is required)."
+                );
+            }
+            return Ok(Vec::new());
+        };
+
+        if config.targets.is_empty() {
+            return Ok(codemodel
+                .find_library(&config.lib_name)
+                .map(|target| vec![target.name.clone()])
+                .unwrap_or_default());
+        }
+        for target in &config.targets {
+            if !codemodel.library_targets().any(|t| &t.name == target) {
+                let available: Vec<&str> = codemodel
+                    .library_targets()
+                    .map(|t| t.name.as_str())
+                    .collect();
+                anyhow::bail!(
+                    "Dependency '{name}' has no CMake library target '{target}'. Library targets: {}",
+                    available.join(", ")
+                );
+            }
+        }
+        Ok(config.targets.clone())
+    }
+
+    fn output(
+        dep_source_dir: &Path,
+        build_dir: &Path,
+        codemodel: Option<&Codemodel>,
+        targets: &[String],
+        config: &CrowDependencyBuild,
+    ) -> Option<DependencyBuildOutput> {
+        let mut output = match codemodel {
+            Some(codemodel) if !targets.is_empty() => codemodel.output(targets)?,
+            _ => {
+                let library_path =
+                    <build_system::builder::BuildSystem as build_system::ToolchainExecutor>::find_library_file(
+                        build_dir,
+                        &config.lib_name,
+                        &config.output_type,
+                    )?;
+                DependencyBuildOutput::new(
+                    config.lib_name.clone(),
+                    std::fs::canonicalize(&library_path).ok()?,
+                    Vec::new(),
                 )
-            })?;
+            }
+        };
 
-        let mut include_paths = vec![".".to_string()];
-        if dep_source_dir.join("include").exists() {
-            include_paths.push("include".to_string());
+        // Targets without include directories expect the source tree on the include path.
+        if DependencyBuildOutput::flatten([&output])
+            .iter()
+            .all(|o| o.include_paths.is_empty())
+        {
+            output.include_paths.push(".".to_string());
+            if dep_source_dir.join("include").exists() {
+                output.include_paths.push("include".to_string());
+            }
         }
+        Some(output)
+    }
 
-        Ok(DependencyBuildOutput {
-            lib_name,
-            library_path: std::fs::canonicalize(&library_path)?,
-            library_dir: std::fs::canonicalize(library_path.parent().unwrap())?,
-            include_paths,
-        })
+    /// Writes a CMake toolchain file with the compilers, tools and flags the package itself
+    /// is built with, so a CMake dependency uses the same compiler and ABI.
+    pub fn write_toolchain_file(
+        toolchain: &ToolchainConfig,
+        profile_config: &BuildProfile,
+        build_dir: &Path,
+        extra_cxx_flags: &[String],
+        logger: &Logger,
+    ) -> anyhow::Result<PathBuf> {
+        let resolve = |tool: &str| {
+            build_system::BuildSystem::resolve_compiler(tool).unwrap_or_else(|| PathBuf::from(tool))
+        };
+        let c_compiler = resolve(&toolchain.compiler_for(Language::C));
+        let cxx_compiler = resolve(&toolchain.compiler);
+        let linker = resolve(&toolchain.linker);
+        let flavor = build_system::BuildSystem::compiler_info(&cxx_compiler, logger).flavor;
+
+        let profile_flags = external::profile_flags(profile_config, flavor);
+
+        let mut lines = vec![
+            "# Generated by crow from the resolved [toolchain] and profile.".to_string(),
+            format!("set(CMAKE_C_COMPILER {})", cmake_string(&c_compiler)),
+            format!("set(CMAKE_CXX_COMPILER {})", cmake_string(&cxx_compiler)),
+            format!(
+                "set(CMAKE_AR {} CACHE FILEPATH \"Archiver\" FORCE)",
+                cmake_string(resolve(&toolchain.archiver))
+            ),
+        ];
+
+        for (language, cmake_language) in [(Language::C, "C"), (Language::Cxx, "CXX")] {
+            let mut flags: Vec<&str> = toolchain
+                .compiler_flags
+                .iter()
+                .chain(toolchain.language_flags(language))
+                .chain(&profile_flags)
+                .map(String::as_str)
+                .collect();
+            if language == Language::Cxx {
+                flags.extend(extra_cxx_flags.iter().map(String::as_str));
+            }
+
+            // Standards go through CMake's own variables, so a project that requires a
+            // newer one still gets it.
+            let std_flag = toolchain.std_flag(language);
+            match std_flag.as_deref().and_then(cmake_standard) {
+                Some((standard, extensions)) => {
+                    lines.push(format!("set(CMAKE_{cmake_language}_STANDARD {standard})"));
+                    lines.push(format!(
+                        "set(CMAKE_{cmake_language}_EXTENSIONS {})",
+                        if extensions { "ON" } else { "OFF" }
+                    ));
+                }
+                None => flags.extend(std_flag.as_deref()),
+            }
+            lines.push(format!(
+                "set(CMAKE_{cmake_language}_FLAGS_INIT {})",
+                cmake_string(flags.join(" "))
+            ));
+        }
+
+        let mut linker_flags = toolchain.linker_flags.clone();
+        if profile_config.lto && flavor == CompilerFlavor::GnuLike {
+            linker_flags.push("-flto".to_string());
+        }
+        // CMake links through the compiler driver, which is pointed at a separate linker.
+        if flavor == CompilerFlavor::GnuLike && linker != cxx_compiler {
+            linker_flags.extend(fuse_ld(&linker));
+        }
+        let linker_flags = cmake_string(linker_flags.join(" "));
+        for kind in ["EXE", "SHARED", "MODULE"] {
+            lines.push(format!(
+                "set(CMAKE_{kind}_LINKER_FLAGS_INIT {linker_flags})"
+            ));
+        }
+        if flavor == CompilerFlavor::MsvcLike || linker != cxx_compiler {
+            lines.push(format!("set(CMAKE_LINKER {})", cmake_string(&linker)));
+        }
+
+        if flavor == CompilerFlavor::MsvcLike {
+            // The same runtime as crow's own compile (`/MT`, `/MTd` for `Debug`).
+            lines.push("set(CMAKE_POLICY_DEFAULT_CMP0091 NEW)".to_string());
+            lines.push(
+                "set(CMAKE_MSVC_RUNTIME_LIBRARY \"MultiThreaded$<$<CONFIG:Debug>:Debug>\")"
+                    .to_string(),
+            );
+        }
+
+        let path = build_dir.join(TOOLCHAIN_FILE);
+        let mut content = lines.join("\n");
+        content.push('\n');
+        std::fs::write(&path, content)?;
+        Ok(path)
+    }
+}
+
+/// `-fuse-ld=` for a linker such as `lld`, `ld.gold` or `/usr/bin/mold`; `None` for a
+/// compiler driver, which CMake can only use through `CMAKE_LINKER`.
+fn fuse_ld(linker: &Path) -> Option<String> {
+    let name = linker.file_name()?.to_str()?;
+    let name = name.strip_suffix(".exe").unwrap_or(name);
+    let kind = name.strip_prefix("ld.").unwrap_or(name);
+    let kind = match kind {
+        "ld" => "bfd",
+        "ld64.lld" => "lld",
+        kind => kind,
+    };
+    ["bfd", "gold", "lld", "mold"]
+        .contains(&kind)
+        .then(|| format!("-fuse-ld={kind}"))
+}
+
+/// `-std=c++17` as (`17`, no extensions), `-std=gnu11` as (`11`, extensions).
+fn cmake_standard(std_flag: &str) -> Option<(&str, bool)> {
+    let std = std_flag.strip_prefix("-std=")?;
+    let (version, extensions) = ["c++", "gnu++", "c", "gnu"].iter().find_map(|prefix| {
+        std.strip_prefix(prefix)
+            .map(|v| (v, prefix.starts_with("gnu")))
+    })?;
+    (!version.is_empty() && version.chars().all(|c| c.is_ascii_digit()))
+        .then_some((version, extensions))
+}
+
+fn cmake_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+}
+
+/// A quoted CMake string. Paths use forward slashes, which CMake accepts on every host.
+fn cmake_string(value: impl AsRef<std::ffi::OsStr>) -> String {
+    let value = value.as_ref().to_string_lossy();
+    let value = if Path::new(value.as_ref()).is_absolute() {
+        value.replace('\\', "/")
+    } else {
+        value.to_string()
+    };
+    format!("\"{}\"", cmake_escape(&value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separate_linkers_are_selected_with_fuse_ld() {
+        for (linker, flag) in [
+            ("lld", Some("-fuse-ld=lld")),
+            ("/usr/bin/ld.lld", Some("-fuse-ld=lld")),
+            ("ld64.lld", Some("-fuse-ld=lld")),
+            ("ld.gold", Some("-fuse-ld=gold")),
+            ("/usr/bin/ld", Some("-fuse-ld=bfd")),
+            ("mold", Some("-fuse-ld=mold")),
+            ("clang++", None),
+            ("/usr/bin/g++", None),
+        ] {
+            assert_eq!(fuse_ld(Path::new(linker)).as_deref(), flag, "{linker}");
+        }
     }
 }
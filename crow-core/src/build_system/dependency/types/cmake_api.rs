@@ -0,0 +1,287 @@
+//! Reads what a configured CMake project produces through the CMake File API
+//! (codemodel v2), so the artifacts, include directories and definitions of a target
+//! don't have to be guessed.
+
+use crate::DependencyBuildOutput;
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const CLIENT: &str = "client-crow";
+const CODEMODEL_QUERY: &str = "codemodel-v2";
+
+/// The targets of one configuration of a configured CMake build directory.
+pub struct Codemodel {
+    build_dir: PathBuf,
+    targets: Vec<Target>,
+}
+
+#[derive(Deserialize)]
+struct CodemodelReply {
+    configurations: Vec<Configuration>,
+}
+
+#[derive(Deserialize)]
+struct Configuration {
+    name: String,
+    targets: Vec<TargetReference>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TargetReference {
+    json_file: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Target {
+    pub name: String,
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    artifacts: Vec<Artifact>,
+    #[serde(default)]
+    compile_groups: Vec<CompileGroup>,
+    #[serde(default)]
+    dependencies: Vec<TargetDependency>,
+}
+
+#[derive(Deserialize)]
+struct Artifact {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct CompileGroup {
+    #[serde(default)]
+    includes: Vec<Include>,
+    #[serde(default)]
+    defines: Vec<Define>,
+}
+
+#[derive(Deserialize)]
+struct Include {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Define {
+    define: String,
+}
+
+#[derive(Deserialize)]
+struct TargetDependency {
+    id: String,
+}
+
+impl Target {
+    /// Static and shared libraries, the targets a package can link against.
+    pub fn is_library(&self) -> bool {
+        matches!(self.kind.as_str(), "STATIC_LIBRARY" | "SHARED_LIBRARY")
+    }
+
+    /// The artifact passed to the linker: the archive or import library rather than
+    /// a `.dll` or `.pdb`.
+    fn link_artifact(&self) -> Option<&Path> {
+        self.artifacts
+            .iter()
+            .map(|artifact| artifact.path.as_path())
+            .find(|path| link_name(path).is_some())
+    }
+}
+
+impl Codemodel {
+    /// Asks CMake to write a codemodel reply the next time the build directory is
+    /// configured.
+    pub fn write_query(build_dir: &Path) -> anyhow::Result<()> {
+        let query_dir = build_dir.join(".cmake/api/v1/query").join(CLIENT);
+        std::fs::create_dir_all(&query_dir)?;
+        std::fs::write(query_dir.join(CODEMODEL_QUERY), "")?;
+        Ok(())
+    }
+
+    pub fn has_reply(build_dir: &Path) -> bool {
+        Self::reply_index(build_dir).is_some()
+    }
+
+    /// The newest reply index; their names sort by the time they were written.
+    fn reply_index(build_dir: &Path) -> Option<PathBuf> {
+        std::fs::read_dir(build_dir.join(".cmake/api/v1/reply"))
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("index-") && name.ends_with(".json"))
+            })
+            .max()
+    }
+
+    /// Reads the codemodel CMake wrote for `build_type`, or `None` if CMake answered
+    /// no query (CMake older than 3.14).
+    pub fn read(build_dir: &Path, build_type: &str) -> anyhow::Result<Option<Self>> {
+        let Some(index_path) = Self::reply_index(build_dir) else {
+            return Ok(None);
+        };
+        let reply_dir = index_path.parent().unwrap_or(build_dir);
+        let index: serde_json::Value = read_json(&index_path)?;
+        let Some(json_file) = index["reply"][CLIENT][CODEMODEL_QUERY]["jsonFile"].as_str() else {
+            return Ok(None);
+        };
+
+        let codemodel: CodemodelReply = read_json(&reply_dir.join(json_file))?;
+        let configuration = codemodel
+            .configurations
+            .iter()
+            .find(|configuration| configuration.name.eq_ignore_ascii_case(build_type))
+            .or_else(|| codemodel.configurations.first());
+        let targets = match configuration {
+            Some(configuration) => configuration
+                .targets
+                .iter()
+                .map(|target| read_json(&reply_dir.join(&target.json_file)))
+                .collect::<anyhow::Result<_>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Some(Codemodel {
+            build_dir: build_dir.to_path_buf(),
+            targets,
+        }))
+    }
+
+    pub fn library_targets(&self) -> impl Iterator<Item = &Target> {
+        self.targets.iter().filter(|target| target.is_library())
+    }
+
+    /// The library target named `name`, or else the one whose artifact links as `-l<name>`.
+    pub fn find_library(&self, name: &str) -> Option<&Target> {
+        self.library_targets()
+            .find(|target| target.name == name)
+            .or_else(|| {
+                self.library_targets().find(|target| {
+                    target
+                        .link_artifact()
+                        .and_then(link_name)
+                        .is_some_and(|link| link == name)
+                })
+            })
+    }
+
+    /// The output of the given targets together with the libraries they link. The first
+    /// target that no other selected target depends on is the head; the remaining ones
+    /// become its dependencies. `None` if an artifact has not been built.
+    pub fn output(&self, targets: &[String]) -> Option<DependencyBuildOutput> {
+        let mut memo = HashMap::new();
+        let mut outputs = Vec::new();
+        for name in targets {
+            let target = self.targets.iter().find(|target| &target.name == name)?;
+            outputs.push(self.target_output(target, &mut memo)?);
+        }
+
+        let covered: Vec<bool> = outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| {
+                outputs.iter().enumerate().any(|(j, other)| {
+                    i != j
+                        && DependencyBuildOutput::flatten(&other.dependencies)
+                            .iter()
+                            .any(|dep| dep.library_path == output.library_path)
+                })
+            })
+            .collect();
+        let mut heads = outputs
+            .into_iter()
+            .zip(covered)
+            .filter(|(_, covered)| !covered)
+            .map(|(output, _)| output);
+        let mut head = heads.next()?;
+        head.dependencies.extend(heads);
+        Some(head)
+    }
+
+    fn target_output(
+        &self,
+        target: &Target,
+        memo: &mut HashMap<String, Option<DependencyBuildOutput>>,
+    ) -> Option<DependencyBuildOutput> {
+        if let Some(output) = memo.get(&target.id) {
+            return output.clone();
+        }
+
+        let output = (|| {
+            let artifact = self.build_dir.join(target.link_artifact()?);
+            if !artifact.exists() {
+                return None;
+            }
+
+            let mut output =
+                DependencyBuildOutput::new(link_name(&artifact)?, artifact, Vec::new());
+            for group in &target.compile_groups {
+                for include in &group.includes {
+                    let include = include.path.to_string_lossy().to_string();
+                    if !output.include_paths.contains(&include) {
+                        output.include_paths.push(include);
+                    }
+                }
+                // `<target>_EXPORTS` is only defined while building a shared library.
+                for define in &group.defines {
+                    if !define.define.ends_with("_EXPORTS")
+                        && !output.defines.contains(&define.define)
+                    {
+                        output.defines.push(define.define.clone());
+                    }
+                }
+            }
+
+            for dependency in &target.dependencies {
+                let Some(dependency) = self
+                    .targets
+                    .iter()
+                    .find(|other| other.id == dependency.id && other.is_library())
+                else {
+                    continue;
+                };
+                let dependency_output = self.target_output(dependency, memo)?;
+                output.dependencies.push(dependency_output);
+            }
+            Some(output)
+        })();
+
+        memo.insert(target.id.clone(), output.clone());
+        output
+    }
+}
+
+/// The name a library artifact is linked by: `libfoo.a`, `libfoo.so.1` and `foo.lib`
+/// all link as `foo`. `None` for files that aren't linked (`.dll`, `.pdb`).
+fn link_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    if let Some(name) = file_name.strip_suffix(".lib") {
+        return Some(name.to_string());
+    }
+    let name = file_name.strip_prefix("lib")?;
+    let name = if let Some(name) = name.strip_suffix(".a") {
+        name
+    } else if let Some(name) = name.strip_suffix(".dylib") {
+        name.split('.').next()?
+    } else {
+        name.rsplit_once(".so")
+            .filter(|(_, version)| version.is_empty() || version.starts_with('.'))?
+            .0
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read CMake File API reply {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse CMake File API reply {}", path.display()))
+}
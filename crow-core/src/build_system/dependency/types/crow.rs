@@ -1,35 +1,80 @@
-use crate::config::{Config, CrowDependencyBuild};
+use super::{BuildContext, DependencyBuilder};
+use crate::build_system::BuildSystem;
+use crate::config::{Config, OutputType, PackageConfig};
 use crate::{build_system, DependencyBuildOutput};
 use anyhow::anyhow;
-use crow_utils::logger::Logger;
 use std::path::Path;
 
 pub struct CrowDependency;
 
-impl CrowDependency {
-    pub fn build(
-        name: &str,
-        dep_source_path: &Path,
-        crow_build_config: &CrowDependencyBuild,
-        current_profile: &str,
-        global_deps: bool,
-        logger: Logger,
-    ) -> anyhow::Result<DependencyBuildOutput> {
-        let dep_crow_toml = dep_source_path.join("crow.toml");
-        if !dep_crow_toml.exists() {
-            anyhow::bail!(
-                "Dependency '{name}' is configured for CRow build, but no `crow.toml` found."
-            );
-        }
+impl DependencyBuilder for CrowDependency {
+    fn name(&self) -> &str {
+        "crow"
+    }
 
-        let dep_config = Config::load(&dep_crow_toml)?;
-        let mut dep_package_config = dep_config.package.clone();
-        dep_package_config.output_type = crow_build_config.output_type.clone();
+    fn detect(&self, source_dir: &Path) -> bool {
+        source_dir.join("crow.toml").exists()
+    }
 
-        let dep_build_system =
-            build_system::BuildSystem::new(dep_config, current_profile, global_deps, logger)?;
+    fn build(&self, ctx: &BuildContext) -> anyhow::Result<DependencyBuildOutput> {
+        let (dep_build_system, dep_package_config) = dependency_build_system(ctx)?;
         dep_build_system
-            .build_internal(Some(1), Some(&dep_package_config))
-            .map_err(|e| anyhow!("Failed to build Crow dependency '{}': {}", name, e))
+            .build_internal(ctx.jobs, Some(&dep_package_config))
+            .map_err(|e| anyhow!("Failed to build Crow dependency '{}': {}", ctx.name, e))
+    }
+
+    fn locate_outputs(&self, ctx: &BuildContext) -> anyhow::Result<Option<DependencyBuildOutput>> {
+        let (dep_build_system, dep_package_config) = dependency_build_system(ctx)?;
+        if dep_package_config.output_type == OutputType::HeaderOnly {
+            return Ok(Some(
+                dep_build_system.package_output(&dep_package_config, None),
+            ));
+        }
+        let library_path = <BuildSystem as build_system::ToolchainExecutor>::find_library_file(
+            &dep_build_system.build_dir(),
+            &dep_package_config.name,
+            &dep_package_config.output_type,
+        );
+        Ok(library_path.map(|library_path| {
+            dep_build_system.package_output(&dep_package_config, Some(library_path))
+        }))
     }
 }
+
+/// The build system of the dependency and the package it builds. It builds into
+/// [`BuildContext::build_dir`], so the build is stamped and found again like the
+/// builds of other build systems.
+fn dependency_build_system(ctx: &BuildContext) -> anyhow::Result<(BuildSystem, PackageConfig)> {
+    let name = ctx.name;
+    let dep_crow_toml = ctx.source_dir.join("crow.toml");
+    if !dep_crow_toml.exists() {
+        anyhow::bail!(
+            "Dependency '{name}' is configured for CRow build, but no `crow.toml` found."
+        );
+    }
+
+    let dep_config = Config::load(&dep_crow_toml)?;
+    let mut dep_package_config = dep_config.package.clone();
+    // A package that declares itself header-only has no library to build.
+    if dep_package_config.output_type != OutputType::HeaderOnly {
+        dep_package_config.output_type = ctx.config.output_type.clone();
+    }
+
+    let mut dep_build_system = BuildSystem::with_dependencies(
+        dep_config,
+        ctx.source_dir,
+        ctx.profile,
+        ctx.global_deps,
+        ctx.offline,
+        ctx.dependency_paths.clone(),
+        ctx.dependency_outputs.clone(),
+        ctx.logger.clone(),
+    )?;
+    dep_build_system.target_dir = ctx
+        .build_dir()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    dep_build_system.workspace_member = None;
+    Ok((dep_build_system, dep_package_config))
+}
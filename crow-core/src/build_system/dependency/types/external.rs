@@ -0,0 +1,148 @@
+//! Helpers shared by the builders that drive an external build tool.
+
+use crate::build_system::toolchain::flags::{CompilerFlavor, FlagsConverter};
+use crate::config::{BuildProfile, CrowDependencyBuild, Language, ToolchainConfig};
+use crate::{build_system, DependencyBuildOutput};
+use anyhow::Context;
+use crow_utils::logger::{LogLevel, Logger};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// `-O<level>` and `-flto` of the profile, converted for MSVC-like compilers.
+pub fn profile_flags(profile_config: &BuildProfile, flavor: CompilerFlavor) -> Vec<String> {
+    let mut flags = vec![OsString::from(format!("-O{}", profile_config.opt_level))];
+    if profile_config.lto {
+        flags.push("-flto".into());
+    }
+    if flavor == CompilerFlavor::MsvcLike {
+        flags = build_system::BuildSystem::convert_args_for_msvc(&flags, Path::new(""));
+        flags.retain(|f| !f.to_string_lossy().starts_with("/Fo"));
+    }
+    flags
+        .iter()
+        .map(|f| f.to_string_lossy().to_string())
+        .collect()
+}
+
+/// The crow toolchain as the environment variables configure scripts, Makefiles and Meson
+/// read: `CC`, `CXX`, `AR`, `CFLAGS`, `CXXFLAGS` and `LDFLAGS`.
+pub fn toolchain_env(
+    toolchain: &ToolchainConfig,
+    profile_config: &BuildProfile,
+    logger: &Logger,
+) -> Vec<(&'static str, String)> {
+    let resolve = |tool: &str| {
+        build_system::BuildSystem::resolve_compiler(tool)
+            .unwrap_or_else(|| PathBuf::from(tool))
+            .to_string_lossy()
+            .to_string()
+    };
+    let cxx_compiler = resolve(&toolchain.compiler);
+    let flavor = build_system::BuildSystem::compiler_info(Path::new(&cxx_compiler), logger).flavor;
+    let profile_flags = profile_flags(profile_config, flavor);
+
+    let language_flags = |language: Language| {
+        toolchain
+            .compiler_flags
+            .iter()
+            .chain(toolchain.language_flags(language))
+            .cloned()
+            .chain(toolchain.std_flag(language))
+            .chain(profile_flags.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut linker_flags = toolchain.linker_flags.clone();
+    if profile_config.lto && flavor == CompilerFlavor::GnuLike {
+        linker_flags.push("-flto".to_string());
+    }
+
+    vec![
+        ("CC", resolve(&toolchain.compiler_for(Language::C))),
+        ("CXX", cxx_compiler.clone()),
+        ("AR", resolve(&toolchain.archiver)),
+        ("CFLAGS", language_flags(Language::C)),
+        ("CXXFLAGS", language_flags(Language::Cxx)),
+        ("LDFLAGS", linker_flags.join(" ")),
+    ]
+}
+
+pub fn num_jobs(jobs: Option<usize>) -> usize {
+    jobs.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    })
+}
+
+/// Runs one step of an external build. Its output is shown if it fails, or always in
+/// verbose mode.
+pub fn run_step(name: &str, step: &str, cmd: &mut Command, logger: &Logger) -> anyhow::Result<()> {
+    if logger.verbose {
+        logger.log(
+            LogLevel::Dim,
+            format!("Running {step} command: {:?}", cmd),
+            2,
+        );
+    }
+
+    cmd.stderr(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+
+    let output = cmd.output().with_context(|| {
+        format!(
+            "Failed to run `{}` for dependency '{name}'",
+            cmd.get_program().to_string_lossy()
+        )
+    })?;
+    let stdout_output = String::from_utf8_lossy(&output.stdout);
+    let stderr_output = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        logger.log(
+            LogLevel::Error,
+            format!(
+                "{step} failed for dependency '{}':\n{} {}",
+                name, stdout_output, stderr_output
+            ),
+            1,
+        );
+        anyhow::bail!("{step} failed for dependency '{}'", name);
+    } else if logger.verbose {
+        if !stdout_output.is_empty() {
+            logger.log(LogLevel::Dim, format!("{step} stdout:"), 2);
+            logger.log(LogLevel::Info, &stdout_output, 2);
+        }
+        if !stderr_output.is_empty() {
+            logger.log(LogLevel::Dim, format!("{step} stderr:"), 2);
+            logger.log(LogLevel::Info, &stderr_output, 2);
+        }
+    }
+    Ok(())
+}
+
+/// The library and headers installed under `prefix` (`lib/` and `include/`).
+pub fn installed_output(
+    prefix: &Path,
+    config: &CrowDependencyBuild,
+) -> Option<DependencyBuildOutput> {
+    let library_path =
+        <build_system::builder::BuildSystem as build_system::ToolchainExecutor>::find_library_file(
+            &prefix.join("lib"),
+            &config.lib_name,
+            &config.output_type,
+        )?;
+    let include_dir = prefix.join("include");
+    let include_paths = if include_dir.exists() {
+        vec![include_dir.to_string_lossy().to_string()]
+    } else {
+        Vec::new()
+    };
+    Some(DependencyBuildOutput::new(
+        config.lib_name.clone(),
+        std::fs::canonicalize(library_path).ok()?,
+        include_paths,
+    ))
+}
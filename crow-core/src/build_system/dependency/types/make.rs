@@ -0,0 +1,93 @@
+use super::{external, BuildContext, DependencyBuilder};
+use crate::{build_system, DependencyBuildOutput};
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct MakeDependency;
+
+impl DependencyBuilder for MakeDependency {
+    fn name(&self) -> &str {
+        "make"
+    }
+
+    fn detect(&self, source_dir: &Path) -> bool {
+        ["GNUmakefile", "makefile", "Makefile"]
+            .iter()
+            .any(|makefile| source_dir.join(makefile).exists())
+    }
+
+    /// A plain Makefile builds in its source tree, so every profile builds a fresh copy.
+    fn configure(&self, ctx: &BuildContext) -> anyhow::Result<()> {
+        let work_dir = Self::work_dir(ctx);
+        if work_dir.exists() {
+            std::fs::remove_dir_all(&work_dir)?;
+        }
+        copy_tree(ctx.source_dir, &work_dir)
+    }
+
+    fn build(&self, ctx: &BuildContext) -> anyhow::Result<DependencyBuildOutput> {
+        let work_dir = Self::work_dir(ctx);
+        let mut make = Command::new("make");
+        make.current_dir(&work_dir)
+            .arg(format!("-j{}", external::num_jobs(ctx.jobs)))
+            .envs(external::toolchain_env(
+                ctx.toolchain,
+                ctx.profile_config,
+                &ctx.logger,
+            ));
+        external::run_step(ctx.name, "make", &mut make, &ctx.logger)?;
+
+        self.locate_outputs(ctx)?.ok_or_else(|| {
+            anyhow!(
+                "Could not find library for '{}' after build in {}",
+                ctx.name,
+                work_dir.display()
+            )
+        })
+    }
+
+    fn locate_outputs(&self, ctx: &BuildContext) -> anyhow::Result<Option<DependencyBuildOutput>> {
+        let Ok(work_dir) = std::fs::canonicalize(Self::work_dir(ctx)) else {
+            return Ok(None);
+        };
+        let library_path =
+            <build_system::builder::BuildSystem as build_system::ToolchainExecutor>::find_library_file(
+                &work_dir,
+                &ctx.config.lib_name,
+                &ctx.config.output_type,
+            );
+        Ok(library_path.map(|library_path| {
+            let mut include_paths = vec![work_dir.to_string_lossy().to_string()];
+            if work_dir.join("include").exists() {
+                include_paths.push(work_dir.join("include").to_string_lossy().to_string());
+            }
+            DependencyBuildOutput::new(ctx.config.lib_name.clone(), library_path, include_paths)
+        }))
+    }
+}
+
+impl MakeDependency {
+    fn work_dir(ctx: &BuildContext) -> PathBuf {
+        ctx.build_dir().join("src")
+    }
+}
+
+/// Copies the sources of a dependency, leaving out its build directories and `.git`.
+fn copy_tree(from: &Path, to: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        if file_name == "_crow_build" || file_name == ".git" {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            copy_tree(&path, &to.join(&file_name))?;
+        } else {
+            std::fs::copy(&path, to.join(&file_name))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use super::{external, BuildContext, DependencyBuilder};
+use crate::config::OutputType;
+use crate::DependencyBuildOutput;
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct MesonDependency;
+
+impl DependencyBuilder for MesonDependency {
+    fn name(&self) -> &str {
+        "meson"
+    }
+
+    fn detect(&self, source_dir: &Path) -> bool {
+        source_dir.join("meson.build").exists()
+    }
+
+    fn configure(&self, ctx: &BuildContext) -> anyhow::Result<()> {
+        let build_dir = std::path::absolute(ctx.build_dir())?;
+        std::fs::create_dir_all(&build_dir)?;
+
+        // Meson reads the toolchain from the environment only when setting up.
+        if build_dir.join("meson-private").exists() {
+            return Ok(());
+        }
+        let buildtype = if ctx.profile == "release" {
+            "release"
+        } else {
+            "debug"
+        };
+        let default_library = match ctx.config.output_type {
+            OutputType::SharedLib => "shared",
+            _ => "static",
+        };
+        let mut setup = Command::new("meson");
+        setup
+            .arg("setup")
+            .arg(&build_dir)
+            .arg(ctx.source_dir)
+            .arg(format!("--buildtype={buildtype}"))
+            .arg(format!("--default-library={default_library}"))
+            .arg(format!("--prefix={}", Self::prefix(ctx)?.display()))
+            .arg("--libdir=lib")
+            .envs(external::toolchain_env(
+                ctx.toolchain,
+                ctx.profile_config,
+                &ctx.logger,
+            ));
+        external::run_step(ctx.name, "Meson setup", &mut setup, &ctx.logger)
+    }
+
+    fn build(&self, ctx: &BuildContext) -> anyhow::Result<DependencyBuildOutput> {
+        let build_dir = ctx.build_dir();
+
+        let mut compile = Command::new("meson");
+        compile.arg("compile").arg("-C").arg(&build_dir);
+        external::run_step(ctx.name, "Meson compile", &mut compile, &ctx.logger)?;
+
+        let mut install = Command::new("meson");
+        install.arg("install").arg("-C").arg(&build_dir);
+        external::run_step(ctx.name, "Meson install", &mut install, &ctx.logger)?;
+
+        let prefix = Self::prefix(ctx)?;
+        external::installed_output(&prefix, ctx.config).ok_or_else(|| {
+            anyhow!(
+                "Could not find library for '{}' after install in {}",
+                ctx.name,
+                prefix.display()
+            )
+        })
+    }
+
+    fn locate_outputs(&self, ctx: &BuildContext) -> anyhow::Result<Option<DependencyBuildOutput>> {
+        Ok(external::installed_output(&Self::prefix(ctx)?, ctx.config))
+    }
+}
+
+impl MesonDependency {
+    fn prefix(ctx: &BuildContext) -> anyhow::Result<PathBuf> {
+        Ok(std::path::absolute(ctx.build_dir().join("install"))?)
+    }
+}
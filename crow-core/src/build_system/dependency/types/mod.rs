@@ -1,2 +1,62 @@
+pub mod autotools;
 pub mod cmake;
+pub mod cmake_api;
 pub mod crow;
+pub mod external;
+pub mod make;
+pub mod meson;
+pub mod registry;
+
+pub use registry::DependencyBuilders;
+
+use crate::config::{BuildProfile, CrowDependencyBuild, ToolchainConfig};
+use crate::DependencyBuildOutput;
+use crow_utils::logger::Logger;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+
+/// Everything a [`DependencyBuilder`] gets to build one dependency.
+pub struct BuildContext<'a> {
+    pub name: &'a str,
+    pub source_dir: &'a Path,
+    pub config: &'a CrowDependencyBuild,
+    pub toolchain: &'a ToolchainConfig,
+    pub profile: &'a str,
+    pub profile_config: &'a BuildProfile,
+    pub jobs: Option<usize>,
+    /// Source paths and outputs of the dependencies the dependency declares itself.
+    pub dependency_paths: IndexMap<String, PathBuf>,
+    pub dependency_outputs: IndexMap<String, DependencyBuildOutput>,
+    pub global_deps: bool,
+    pub offline: bool,
+    pub logger: Logger,
+}
+
+impl BuildContext<'_> {
+    /// `_crow_build/<profile>` in the dependency. It is removed when the build inputs change.
+    pub fn build_dir(&self) -> PathBuf {
+        self.source_dir.join("_crow_build").join(self.profile)
+    }
+}
+
+/// Builds the dependencies of one build system. Builders are looked up in
+/// [`DependencyBuilders`] by the `build_system` of a dependency.
+pub trait DependencyBuilder: Send + Sync {
+    /// The `build_system` value this builder handles.
+    fn name(&self) -> &str;
+
+    /// Whether the project in `source_dir` uses this build system.
+    fn detect(&self, source_dir: &Path) -> bool;
+
+    /// Prepares the build directory. Runs before every `build`.
+    fn configure(&self, _ctx: &BuildContext) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Builds the dependency and returns what its consumers compile and link with.
+    fn build(&self, ctx: &BuildContext) -> anyhow::Result<DependencyBuildOutput>;
+
+    /// The outputs of a previous build with the same inputs, or `None` if it has to be
+    /// built again.
+    fn locate_outputs(&self, ctx: &BuildContext) -> anyhow::Result<Option<DependencyBuildOutput>>;
+}
@@ -0,0 +1,64 @@
+use super::autotools::AutotoolsDependency;
+use super::cmake::CmakeDependency;
+use super::crow::CrowDependency;
+use super::make::MakeDependency;
+use super::meson::MesonDependency;
+use super::DependencyBuilder;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+static BUILDERS: OnceLock<RwLock<Vec<Arc<dyn DependencyBuilder>>>> = OnceLock::new();
+
+/// The dependency builders of the process: the built-in ones plus any registered by a
+/// program that embeds crow-core.
+pub struct DependencyBuilders;
+
+impl DependencyBuilders {
+    fn builders() -> &'static RwLock<Vec<Arc<dyn DependencyBuilder>>> {
+        BUILDERS.get_or_init(|| {
+            RwLock::new(vec![
+                Arc::new(CrowDependency),
+                Arc::new(CmakeDependency),
+                Arc::new(MesonDependency),
+                Arc::new(AutotoolsDependency),
+                Arc::new(MakeDependency),
+            ])
+        })
+    }
+
+    /// Adds `builder`, replacing a builder with the same name. Registered builders are
+    /// asked before the built-in ones when detecting a build system.
+    pub fn register(builder: impl DependencyBuilder + 'static) {
+        let mut builders = Self::builders().write().unwrap();
+        builders.retain(|existing| existing.name() != builder.name());
+        builders.insert(0, Arc::new(builder));
+    }
+
+    pub fn get(name: &str) -> Option<Arc<dyn DependencyBuilder>> {
+        Self::builders()
+            .read()
+            .unwrap()
+            .iter()
+            .find(|builder| builder.name() == name)
+            .cloned()
+    }
+
+    /// The first builder that recognizes the project in `source_dir`.
+    pub fn detect(source_dir: &Path) -> Option<Arc<dyn DependencyBuilder>> {
+        Self::builders()
+            .read()
+            .unwrap()
+            .iter()
+            .find(|builder| builder.detect(source_dir))
+            .cloned()
+    }
+
+    pub fn names() -> Vec<String> {
+        Self::builders()
+            .read()
+            .unwrap()
+            .iter()
+            .map(|builder| builder.name().to_string())
+            .collect()
+    }
+}
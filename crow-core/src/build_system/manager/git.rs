@@ -13,6 +13,54 @@ pub trait GitManager {
         logger: &Logger,
     ) -> anyhow::Result<()>;
     fn git_pull(repo_path: &Path, logger: &Logger) -> anyhow::Result<()>;
+    fn git_head_rev(repo_path: &Path) -> anyhow::Result<String>;
+    fn git_fetch_latest(repo_path: &Path, branch: &str, logger: &Logger) -> anyhow::Result<()>;
+    fn git_has_rev(repo_path: &Path, rev: &str) -> bool;
+    fn git_checkout_rev(repo_path: &Path, rev: &str, logger: &Logger) -> anyhow::Result<()>;
+    fn git_update_submodules(
+        repo_path: &Path,
+        offline: bool,
+        logger: &Logger,
+    ) -> anyhow::Result<()>;
+}
+
+fn run_git(repo_path: &Path, args: &[&str], logger: &Logger) -> anyhow::Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo_path).args(args);
+    cmd.stderr(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+
+    let output = cmd
+        .output()
+        .with_context(|| format!("Failed while executing `git {}`", args.join(" ")))?;
+    let stdout_output = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr_output = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        if logger.verbose {
+            logger.log(
+                LogLevel::Dim,
+                format!(
+                    "`git {}` failed in '{}':\n{} {}",
+                    args.join(" "),
+                    repo_path.display(),
+                    stdout_output,
+                    stderr_output
+                ),
+                2,
+            );
+        }
+        anyhow::bail!(
+            "`git {}` failed in '{}': {}",
+            args.join(" "),
+            repo_path.display(),
+            stderr_output.trim()
+        );
+    } else if logger.verbose && !stderr_output.is_empty() {
+        logger.log(LogLevel::Dim, format!("git {}:", args.join(" ")), 2);
+        logger.log((), &stderr_output, 2);
+    }
+    Ok(stdout_output)
 }
 
 impl GitManager for BuildSystem {
@@ -108,4 +156,89 @@ impl GitManager for BuildSystem {
         }
         Ok(())
     }
+
+    fn git_head_rev(repo_path: &Path) -> anyhow::Result<String> {
+        let rev = run_git(repo_path, &["rev-parse", "HEAD"], &Logger::new())?;
+        Ok(rev.trim().to_string())
+    }
+
+    fn git_fetch_latest(repo_path: &Path, branch: &str, logger: &Logger) -> anyhow::Result<()> {
+        let refspec = if branch.is_empty() { "HEAD" } else { branch };
+        run_git(
+            repo_path,
+            &["fetch", "--depth", "1", "origin", refspec],
+            logger,
+        )
+        .with_context(|| {
+            format!(
+                "Failed while fetching updates for '{}'",
+                repo_path.display()
+            )
+        })?;
+        run_git(
+            repo_path,
+            &["checkout", "--quiet", "--detach", "FETCH_HEAD"],
+            logger,
+        )?;
+        Ok(())
+    }
+
+    fn git_has_rev(repo_path: &Path, rev: &str) -> bool {
+        let commit = format!("{}^{{commit}}", rev);
+        run_git(repo_path, &["cat-file", "-e", &commit], &Logger::new()).is_ok()
+    }
+
+    fn git_checkout_rev(repo_path: &Path, rev: &str, logger: &Logger) -> anyhow::Result<()> {
+        if !BuildSystem::git_has_rev(repo_path, rev) {
+            // Clones are shallow; most hosts allow fetching a single commit by its SHA.
+            // If that is refused, fall back to fetching the full history.
+            if run_git(repo_path, &["fetch", "--depth", "1", "origin", rev], logger).is_err() {
+                let is_shallow =
+                    run_git(repo_path, &["rev-parse", "--is-shallow-repository"], logger)?;
+                if is_shallow.trim() == "true" {
+                    run_git(
+                        repo_path,
+                        &["fetch", "--unshallow", "--tags", "origin"],
+                        logger,
+                    )?;
+                } else {
+                    run_git(repo_path, &["fetch", "--tags", "origin"], logger)?;
+                }
+            }
+        }
+
+        run_git(repo_path, &["checkout", "--quiet", "--detach", rev], logger).with_context(
+            || format!("Revision '{}' not found in '{}'", rev, repo_path.display()),
+        )?;
+        Ok(())
+    }
+
+    fn git_update_submodules(
+        repo_path: &Path,
+        offline: bool,
+        logger: &Logger,
+    ) -> anyhow::Result<()> {
+        if !repo_path.join(".gitmodules").exists() {
+            return Ok(());
+        }
+        let args: &[&str] = if offline {
+            &["submodule", "update", "--init", "--recursive", "--no-fetch"]
+        } else {
+            &[
+                "submodule",
+                "update",
+                "--init",
+                "--recursive",
+                "--depth",
+                "1",
+            ]
+        };
+        run_git(repo_path, args, logger).with_context(|| {
+            format!(
+                "Failed while fetching submodules of '{}'",
+                repo_path.display()
+            )
+        })?;
+        Ok(())
+    }
 }
@@ -1,13 +1,22 @@
 mod builder;
 mod dependency;
 mod manager;
+mod tester;
 mod toolchain;
+mod workspace;
 
+pub use builder::compdb::{CompilationDatabase, CompileCommand};
 pub use builder::incremental::*;
+pub use builder::pkg_config::PkgConfigWriter;
 pub use builder::BuildSystem;
-pub use dependency::{DependencyBuildOutput, DependencyResolver};
+pub use dependency::{
+    BuildContext, DependencyBuildOutput, DependencyBuilder, DependencyBuilders, DependencyGraph,
+    DependencyNode, DependencyRequester, DependencyResolver, DependencySource, SystemDependency,
+};
 pub use manager::GitManager;
+pub use tester::{TestResult, TestRunner};
 pub use toolchain::ToolchainExecutor;
+pub use workspace::{Workspace, WorkspaceMember};
 
 use crate::config::{BuildProfile, Config, PackageConfig, ToolchainConfig};
 use crow_utils::logger::Logger;
@@ -0,0 +1,243 @@
+use super::*;
+use crate::build_system::ToolchainExecutor;
+use crate::config::{OutputType, TestTarget};
+use crate::utils;
+use crow_utils::logger::LogLevel;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub name: String,
+    pub passed: bool,
+    pub duration: Duration,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub trait TestRunner {
+    fn discover_tests(&self) -> anyhow::Result<Vec<TestTarget>>;
+    fn build_tests(
+        &self,
+        jobs: Option<usize>,
+        filter: Option<&str>,
+    ) -> anyhow::Result<Vec<(TestTarget, PathBuf)>>;
+    fn run_tests(
+        &self,
+        tests: &[(TestTarget, PathBuf)],
+        jobs: Option<usize>,
+    ) -> anyhow::Result<Vec<TestResult>>;
+}
+
+impl TestRunner for BuildSystem {
+    fn discover_tests(&self) -> anyhow::Result<Vec<TestTarget>> {
+        if !self.config.tests.is_empty() {
+            for test in &self.config.tests {
+                if test.name.is_empty() {
+                    anyhow::bail!("Every [[test]] entry in crow.toml needs a `name`.");
+                }
+                if test.sources.is_empty() {
+                    anyhow::bail!("Test '{}' has no `sources`.", test.name);
+                }
+            }
+            return Ok(self.config.tests.clone());
+        }
+
+        // No [[test]] entries: every file in tests/ is a standalone test.
+        let mut tests = Vec::new();
+        let escaped_root = PathBuf::from(glob::Pattern::escape(&self.root.to_string_lossy()));
+        for pattern in ["tests/*.cpp", "tests/*.c"] {
+            for entry in glob::glob(&escaped_root.join(pattern).to_string_lossy())? {
+                let path = entry?;
+                let path = path
+                    .strip_prefix(&self.root)
+                    .map_or(path.clone(), |p| p.to_path_buf());
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                tests.push(TestTarget {
+                    name: name.to_string(),
+                    sources: vec![glob::Pattern::escape(&path.to_string_lossy())],
+                    ..TestTarget::default()
+                });
+            }
+        }
+        tests.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tests)
+    }
+
+    fn build_tests(
+        &self,
+        jobs: Option<usize>,
+        filter: Option<&str>,
+    ) -> anyhow::Result<Vec<(TestTarget, PathBuf)>> {
+        let tests: Vec<TestTarget> = self
+            .discover_tests()?
+            .into_iter()
+            .filter(|t| filter.is_none_or(|f| t.name.contains(f)))
+            .collect();
+        if tests.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.logger.log(
+            LogLevel::Bold,
+            format!(
+                "Building {} test(s) for `{}` (profile: {})...",
+                tests.len(),
+                self.package_config.name,
+                self.profile_name
+            ),
+            1,
+        );
+
+        let tests_dir = self.intermediate_dir().join("tests");
+        std::fs::create_dir_all(&tests_dir)?;
+
+        // Tests link against the package objects. An executable package brings its own
+        // `main`, so its `main.*` sources are left out.
+        let mut package_sources = utils::find_source_files(&self.package_config, &self.root)?;
+        if self.package_config.output_type == OutputType::Executable {
+            package_sources.retain(|s| s.file_stem().is_none_or(|stem| stem != "main"));
+        }
+        let package_objects = if package_sources.is_empty() {
+            Vec::new()
+        } else {
+            let package = PackageConfig {
+                sources: package_sources
+                    .iter()
+                    .map(|s| glob::Pattern::escape(&s.to_string_lossy()))
+                    .collect(),
+                ..self.package_config.clone()
+            };
+            self.compile_sources(jobs, &package)?
+        };
+
+        let mut binaries = Vec::new();
+        for test in tests {
+            let mut includes = self.package_config.includes.clone();
+            includes.extend(test.includes.iter().cloned());
+
+            let test_package = PackageConfig {
+                name: test.name.clone(),
+                output_type: OutputType::Executable,
+                sources: test.sources.clone(),
+                includes,
+                ..self.package_config.clone()
+            };
+
+            let mut objects = self.compile_sources(jobs, &test_package)?;
+            objects.extend(package_objects.iter().cloned());
+
+            let exe_path = tests_dir.join(&test.name);
+            self.link_executable(&objects, &exe_path)?;
+            <BuildSystem as ToolchainExecutor>::set_executable_permissions(&exe_path)?;
+            if self.logger.verbose {
+                self.logger.log(
+                    LogLevel::Dim,
+                    format!("Built test '{}' at {}", test.name, exe_path.display()),
+                    2,
+                );
+            }
+            binaries.push((test, exe_path));
+        }
+        Ok(binaries)
+    }
+
+    fn run_tests(
+        &self,
+        tests: &[(TestTarget, PathBuf)],
+        jobs: Option<usize>,
+    ) -> anyhow::Result<Vec<TestResult>> {
+        let num_jobs = jobs.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
+        let pool = threadpool::ThreadPool::new(num_jobs);
+        let (tx, rx) = mpsc::channel();
+
+        self.logger.log(
+            LogLevel::Bold,
+            format!("Running {} test(s)...", tests.len()),
+            1,
+        );
+
+        for (test, exe_path) in tests {
+            let tx = tx.clone();
+            let name = test.name.clone();
+            let args = test.args.clone();
+            let exe_path = exe_path.clone();
+            let root = self.root.clone();
+
+            pool.execute(move || {
+                let started = Instant::now();
+                let result = match Command::new(&exe_path)
+                    .args(&args)
+                    .current_dir(&root)
+                    .output()
+                {
+                    Ok(output) => TestResult {
+                        name,
+                        passed: output.status.success(),
+                        duration: started.elapsed(),
+                        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                    },
+                    Err(e) => TestResult {
+                        name,
+                        passed: false,
+                        duration: started.elapsed(),
+                        stdout: String::new(),
+                        stderr: format!("Cannot run '{}': {}", exe_path.display(), e),
+                    },
+                };
+                tx.send(result).unwrap();
+            });
+        }
+
+        drop(tx);
+        let mut results = Vec::new();
+        for result in rx.iter() {
+            if result.passed {
+                self.logger.log(
+                    LogLevel::Custom("\x1b[32m"),
+                    format!(
+                        "[PASS] {} ({:.2}s)",
+                        result.name,
+                        result.duration.as_secs_f64()
+                    ),
+                    2,
+                );
+            } else {
+                self.logger.log(
+                    LogLevel::Custom("\x1b[31m"),
+                    format!(
+                        "[FAIL] {} ({:.2}s)",
+                        result.name,
+                        result.duration.as_secs_f64()
+                    ),
+                    2,
+                );
+            }
+
+            if !result.passed || self.logger.verbose {
+                if !result.stdout.is_empty() {
+                    self.logger.log(LogLevel::Dim, "stdout:", 3);
+                    self.logger.log((), &result.stdout, 3);
+                }
+                if !result.stderr.is_empty() {
+                    self.logger.log(LogLevel::Dim, "stderr:", 3);
+                    self.logger.log((), &result.stderr, 3);
+                }
+            }
+            results.push(result);
+        }
+
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(results)
+    }
+}
@@ -0,0 +1,312 @@
+use super::flags::CompilerFlavor;
+use crow_utils::logger::{LogLevel, Logger};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompilerVendor {
+    Gcc,
+    Clang,
+    AppleClang,
+    Intel,
+    Msvc,
+    ClangCl,
+    Unknown,
+}
+
+impl std::fmt::Display for CompilerVendor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompilerVendor::Gcc => write!(f, "GCC"),
+            CompilerVendor::Clang => write!(f, "Clang"),
+            CompilerVendor::AppleClang => write!(f, "Apple Clang"),
+            CompilerVendor::Intel => write!(f, "Intel"),
+            CompilerVendor::Msvc => write!(f, "MSVC"),
+            CompilerVendor::ClangCl => write!(f, "clang-cl"),
+            CompilerVendor::Unknown => write!(f, "unknown compiler"),
+        }
+    }
+}
+
+/// What a compiler executable turned out to be when it was run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilerInfo {
+    pub path: PathBuf,
+    pub flavor: CompilerFlavor,
+    pub vendor: CompilerVendor,
+    pub version: String,
+    /// Changes whenever a different or upgraded compiler is behind `path`.
+    pub fingerprint: u64,
+}
+
+static DETECTED: OnceLock<Mutex<HashMap<PathBuf, CompilerInfo>>> = OnceLock::new();
+
+impl CompilerInfo {
+    /// Probes `compiler_path` once per process; later calls return the cached result.
+    pub fn detect(compiler_path: &Path, logger: &Logger) -> CompilerInfo {
+        let detected = DETECTED.get_or_init(|| Mutex::new(HashMap::new()));
+        if let Some(info) = detected.lock().unwrap().get(compiler_path) {
+            return info.clone();
+        }
+
+        let mut info = CompilerInfo::probe(compiler_path);
+        info.fingerprint = fingerprint(compiler_path);
+        if info.vendor == CompilerVendor::Unknown {
+            logger.log(
+                LogLevel::Warn,
+                format!(
+                    "Could not determine compiler type for '{}'. Defaulting to GNU-like",
+                    compiler_path.display()
+                ),
+                1,
+            );
+        } else if logger.verbose {
+            logger.log(
+                LogLevel::Dim,
+                format!(
+                    "Detected {} {} ({}) at '{}'",
+                    info.vendor,
+                    info.version,
+                    info.flavor,
+                    compiler_path.display()
+                ),
+                2,
+            );
+        }
+        detected
+            .lock()
+            .unwrap()
+            .insert(compiler_path.to_path_buf(), info.clone());
+        info
+    }
+
+    fn probe(compiler_path: &Path) -> CompilerInfo {
+        let info = |flavor, vendor, version: String| CompilerInfo {
+            path: compiler_path.to_path_buf(),
+            flavor,
+            vendor,
+            version,
+            fingerprint: 0,
+        };
+
+        // GCC-style drivers dump their predefined macros.
+        if let Some(output) = run(compiler_path, &["-dM", "-E", "-x", "c", "-"]) {
+            if let Some((flavor, vendor, version)) = classify_macros(&parse_macros(&output)) {
+                return info(flavor, vendor, version);
+            }
+        }
+
+        // cl.exe and clang-cl reject the probe above but answer `/?`.
+        if let Some(output) = run_any(compiler_path, &["/?"]) {
+            if output.contains("Microsoft") && output.contains("Compiler") {
+                let version = version_after(&output, "Version ").unwrap_or_default();
+                return info(CompilerFlavor::MsvcLike, CompilerVendor::Msvc, version);
+            }
+            if output.contains("clang-cl") || output.contains("CL.EXE COMPATIBILITY") {
+                let version = run_any(compiler_path, &["--version"])
+                    .and_then(|out| version_after(&out, "clang version "))
+                    .unwrap_or_default();
+                return info(CompilerFlavor::MsvcLike, CompilerVendor::ClangCl, version);
+            }
+        }
+
+        if let Some(output) = run_any(compiler_path, &["--version"]) {
+            if let Some(version) = version_after(&output, "clang version ") {
+                return info(CompilerFlavor::GnuLike, CompilerVendor::Clang, version);
+            }
+            if output.contains("Free Software Foundation") {
+                let version = output
+                    .lines()
+                    .next()
+                    .and_then(|line| line.split_whitespace().last())
+                    .unwrap_or_default()
+                    .to_string();
+                return info(CompilerFlavor::GnuLike, CompilerVendor::Gcc, version);
+            }
+        }
+
+        info(
+            CompilerFlavor::GnuLike,
+            CompilerVendor::Unknown,
+            String::new(),
+        )
+    }
+}
+
+/// Hash of the resolved executable's path, its `--version` output and its contents.
+fn fingerprint(compiler_path: &Path) -> u64 {
+    let resolved = std::fs::canonicalize(compiler_path).unwrap_or(compiler_path.to_path_buf());
+    let mut hasher = xxhash_rust::xxh3::Xxh3::default();
+    hasher.update(resolved.to_string_lossy().as_bytes());
+    if let Some(version) = run_any(compiler_path, &["--version"]) {
+        hasher.update(version.as_bytes());
+    }
+    if let Ok(binary) = std::fs::read(&resolved) {
+        hasher.update(&xxhash_rust::xxh3::xxh3_64(&binary).to_le_bytes());
+    }
+    hasher.digest()
+}
+
+/// Output of a successful run.
+fn run(compiler_path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new(compiler_path)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Stdout and stderr of a run, whatever its exit status.
+fn run_any(compiler_path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new(compiler_path)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    Some(format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    ))
+}
+
+/// The compiler that predefines `macros`. `_MSC_VER` is checked first: clang-cl defines
+/// it next to `__clang__`, but takes MSVC-style flags.
+fn classify_macros(
+    macros: &HashMap<String, String>,
+) -> Option<(CompilerFlavor, CompilerVendor, String)> {
+    let version = |parts: [&str; 3]| {
+        parts
+            .iter()
+            .map(|name| macros.get(*name).map_or("0", String::as_str))
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+    let clang_version = || version(["__clang_major__", "__clang_minor__", "__clang_patchlevel__"]);
+    if let Some(msc_version) = macros.get("_MSC_VER") {
+        return Some(if macros.contains_key("__clang__") {
+            (
+                CompilerFlavor::MsvcLike,
+                CompilerVendor::ClangCl,
+                clang_version(),
+            )
+        } else {
+            (
+                CompilerFlavor::MsvcLike,
+                CompilerVendor::Msvc,
+                msc_version.clone(),
+            )
+        });
+    }
+    if macros.contains_key("__INTEL_LLVM_COMPILER") {
+        return Some((
+            CompilerFlavor::GnuLike,
+            CompilerVendor::Intel,
+            clang_version(),
+        ));
+    }
+    if macros.contains_key("__apple_build_version__") {
+        return Some((
+            CompilerFlavor::GnuLike,
+            CompilerVendor::AppleClang,
+            clang_version(),
+        ));
+    }
+    if macros.contains_key("__clang__") {
+        return Some((
+            CompilerFlavor::GnuLike,
+            CompilerVendor::Clang,
+            clang_version(),
+        ));
+    }
+    if macros.contains_key("__GNUC__") {
+        return Some((
+            CompilerFlavor::GnuLike,
+            CompilerVendor::Gcc,
+            version(["__GNUC__", "__GNUC_MINOR__", "__GNUC_PATCHLEVEL__"]),
+        ));
+    }
+    None
+}
+
+fn parse_macros(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.strip_prefix("#define ")?.splitn(2, ' ');
+            let name = parts.next()?.to_string();
+            Some((name, parts.next().unwrap_or_default().to_string()))
+        })
+        .collect()
+}
+
+/// The dotted version number following `marker`, e.g. `17.0.6` in "clang version 17.0.6".
+fn version_after(output: &str, marker: &str) -> Option<String> {
+    let start = output.find(marker)? + marker.len();
+    let version: String = output[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(defines: &str) -> Option<(CompilerFlavor, CompilerVendor, String)> {
+        classify_macros(&parse_macros(defines))
+    }
+
+    #[test]
+    fn clang_cl_is_msvc_like() {
+        assert_eq!(
+            classify(
+                "#define __clang__ 1\n#define __clang_major__ 17\n#define __clang_minor__ 0\n\
+                 #define __clang_patchlevel__ 6\n#define __GNUC__ 4\n#define _MSC_VER 1937\n"
+            ),
+            Some((
+                CompilerFlavor::MsvcLike,
+                CompilerVendor::ClangCl,
+                "17.0.6".to_string()
+            ))
+        );
+        assert_eq!(
+            classify("#define _MSC_VER 1937\n"),
+            Some((
+                CompilerFlavor::MsvcLike,
+                CompilerVendor::Msvc,
+                "1937".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn gnu_drivers_are_gnu_like() {
+        assert_eq!(
+            classify("#define __clang__ 1\n#define __clang_major__ 18\n#define __GNUC__ 4\n"),
+            Some((
+                CompilerFlavor::GnuLike,
+                CompilerVendor::Clang,
+                "18.0.0".to_string()
+            ))
+        );
+        assert_eq!(
+            classify(
+                "#define __GNUC__ 13\n#define __GNUC_MINOR__ 2\n#define __GNUC_PATCHLEVEL__ 0\n"
+            ),
+            Some((
+                CompilerFlavor::GnuLike,
+                CompilerVendor::Gcc,
+                "13.2.0".to_string()
+            ))
+        );
+        assert_eq!(classify("#define __STDC__ 1\n"), None);
+    }
+}
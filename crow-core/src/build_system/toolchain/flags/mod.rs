@@ -1,5 +1,6 @@
+use super::compiler::CompilerInfo;
 use super::*;
-use crow_utils::logger::{LogLevel, Logger};
+use crow_utils::logger::Logger;
 use crow_utils::Environment;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
@@ -7,16 +8,36 @@ use std::path::{Path, PathBuf};
 pub trait FlagsConverter {
     fn find_executable_in_path(name: &str) -> Option<PathBuf>;
     fn resolve_compiler(compiler: &str) -> Option<PathBuf>;
-    fn detect_compiler_flavour(compiler_path: &Path, logger: &Logger) -> CompilerFlavor;
+    fn compiler_info(compiler_path: &Path, logger: &Logger) -> CompilerInfo;
     fn convert_args_for_msvc(args: &[OsString], output: &Path) -> Vec<OsString>;
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompilerFlavor {
     GnuLike,  // GCC Clang
     MsvcLike, // MSVC clang-cl
 }
 
+impl std::fmt::Display for CompilerFlavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompilerFlavor::GnuLike => write!(f, "GNU-like"),
+            CompilerFlavor::MsvcLike => write!(f, "MSVC-like"),
+        }
+    }
+}
+
+/// The MSVC runtime crow compiles with: the static runtime, in its debug variant for
+/// every profile but `release`. CMake dependencies are configured as `Debug` for the same
+/// profiles and select the matching `CMAKE_MSVC_RUNTIME_LIBRARY`.
+pub fn msvc_runtime_flag(profile_name: &str) -> &'static str {
+    if profile_name == "release" {
+        "/MT"
+    } else {
+        "/MTd"
+    }
+}
+
 impl FlagsConverter for BuildSystem {
     fn find_executable_in_path(name: &str) -> Option<PathBuf> {
         if name.contains(std::path::MAIN_SEPARATOR) {
@@ -58,47 +79,8 @@ impl FlagsConverter for BuildSystem {
         None
     }
 
-    fn detect_compiler_flavour(compiler_path: &Path, logger: &Logger) -> CompilerFlavor {
-        if logger.verbose {
-            logger.log(LogLevel::Dim, "Determining compiler flavor by file name", 2);
-        }
-        if let Some(fname_os) = compiler_path.file_name() {
-            if let Some(fname) = fname_os.to_str() {
-                let f = fname.to_lowercase();
-
-                if f.contains("clang-cl") || f == "cl.exe" || f == "clang++" {
-                    logger.log(
-                        LogLevel::Dim,
-                        &format!("Detected MSVC-like flavor ('{}')", f),
-                        2,
-                    );
-                    return CompilerFlavor::MsvcLike;
-                }
-                if f.contains("clang")
-                    || f.contains("g++")
-                    || f.contains("gcc")
-                    || f.contains("cc")
-                    || f.contains("c++")
-                {
-                    logger.log(
-                        LogLevel::Dim,
-                        &format!("Detected GNU-like flavor ('{}')", f),
-                        2,
-                    );
-                    return CompilerFlavor::GnuLike;
-                }
-            }
-        }
-
-        logger.log(
-            LogLevel::Warn,
-            &format!(
-                "Could not determine compiler type for '{}'. Defaulting to GNU-like",
-                compiler_path.display()
-            ),
-            1,
-        );
-        CompilerFlavor::GnuLike
+    fn compiler_info(compiler_path: &Path, logger: &Logger) -> CompilerInfo {
+        CompilerInfo::detect(compiler_path, logger)
     }
 
     fn convert_args_for_msvc(args: &[OsString], output: &Path) -> Vec<OsString> {
@@ -133,6 +115,11 @@ impl FlagsConverter for BuildSystem {
                 || s == "-std=c++2a"
             {
                 out.push(OsString::from("/std:c++17"));
+            } else if let Some(version) = s
+                .strip_prefix("-std=c")
+                .filter(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_digit()))
+            {
+                out.push(OsString::from(format!("/std:c{}", version)));
             } else if s.starts_with("-O") && s.len() >= 2 {
                 let lvl = &s[2..];
                 match lvl {
@@ -1,4 +1,5 @@
-mod flags;
+pub(crate) mod compiler;
+pub(crate) mod flags;
 
 use self::flags::{CompilerFlavor, FlagsConverter};
 use super::*;
@@ -11,12 +12,15 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 pub trait ToolchainExecutor {
+    #[allow(clippy::too_many_arguments)]
     fn compile_with_args(
         compiler: &str,
         args: &[std::ffi::OsString],
+        root: &Path,
         source: &Path,
         output: &Path,
         incremental: bool,
+        profile_name: &str,
         logger: &Logger,
     ) -> anyhow::Result<(PathBuf, u64)>;
 
@@ -31,12 +35,15 @@ pub trait ToolchainExecutor {
 }
 
 impl ToolchainExecutor for BuildSystem {
+    #[allow(clippy::too_many_arguments)]
     fn compile_with_args(
         compiler: &str,
         args: &[std::ffi::OsString],
+        root: &Path,
         source: &Path,
         output: &Path,
         incremental: bool,
+        profile_name: &str,
         logger: &Logger,
     ) -> anyhow::Result<(PathBuf, u64)> {
         let compiler_path = if let Some(resolved) = BuildSystem::resolve_compiler(compiler) {
@@ -45,13 +52,15 @@ impl ToolchainExecutor for BuildSystem {
             PathBuf::from(compiler)
         };
 
-        let flavour = BuildSystem::detect_compiler_flavour(&compiler_path, logger);
+        let flavour = BuildSystem::compiler_info(&compiler_path, logger).flavor;
 
         let mut cmd = Command::new(&compiler_path);
+        cmd.current_dir(root);
 
         // If compiler is MSVC-like - convert flags
         if flavour == CompilerFlavor::MsvcLike {
-            let msvc_args = BuildSystem::convert_args_for_msvc(args, output);
+            let mut msvc_args = BuildSystem::convert_args_for_msvc(args, output);
+            msvc_args.push(flags::msvc_runtime_flag(profile_name).into());
             cmd.args(&msvc_args);
             if logger.verbose {
                 logger.log(
@@ -109,7 +118,7 @@ impl ToolchainExecutor for BuildSystem {
         }
 
         let deps_hash = if incremental {
-            BuildCache::parse_dep_file(&output.with_extension("d"))
+            BuildCache::parse_dep_file(&output.with_extension("d"), root)
                 .and_then(|deps| BuildCache::compute_deps_hash(&deps))
                 .unwrap_or(0)
         } else {
@@ -127,6 +136,7 @@ impl ToolchainExecutor for BuildSystem {
             );
         }
         let mut cmd = Command::new(&self.toolchain.linker);
+        cmd.current_dir(&self.root);
         objects.iter().for_each(|o| {
             cmd.arg(o);
         });
@@ -139,47 +149,7 @@ impl ToolchainExecutor for BuildSystem {
             cmd.arg(format!("-O{}", self.profile_config.opt_level));
         }
 
-        for lib_dir in &self.package_config.lib_dirs {
-            if self.logger.verbose {
-                self.logger.log(
-                    LogLevel::Dim,
-                    &format!("-L '{}' (from crow.toml)", lib_dir),
-                    2,
-                );
-            }
-            cmd.arg(format!("-L{}", lib_dir));
-        }
-        for (name, build_output) in &self.dep_build_outputs {
-            if self.logger.verbose {
-                self.logger.log(
-                    LogLevel::Dim,
-                    &format!(
-                        "-L '{}' (from dependency '{name}')",
-                        build_output.library_dir.display()
-                    ),
-                    2,
-                );
-            }
-            cmd.arg(format!("-L{}", build_output.library_dir.display()));
-        }
-
-        for lib in &self.package_config.libs {
-            if self.logger.verbose {
-                self.logger
-                    .log(LogLevel::Dim, &format!("-l '{}' (from crow.toml)", lib), 2);
-            }
-            cmd.arg(format!("-l{}", lib));
-        }
-        for (name, build_output) in &self.dep_build_outputs {
-            if self.logger.verbose {
-                self.logger.log(
-                    LogLevel::Dim,
-                    &format!("-l '{}' (from dependency '{name}')", build_output.lib_name),
-                    2,
-                );
-            }
-            cmd.arg(format!("-l{}", build_output.lib_name));
-        }
+        self.append_link_inputs(&mut cmd);
 
         cmd.arg("-o").arg(output);
 
@@ -215,6 +185,7 @@ impl ToolchainExecutor for BuildSystem {
             );
         }
         let mut cmd = Command::new(&self.toolchain.archiver);
+        cmd.current_dir(&self.root);
         self.toolchain.archiver_flags.iter().for_each(|f| {
             cmd.arg(f);
         });
@@ -255,6 +226,7 @@ impl ToolchainExecutor for BuildSystem {
             );
         }
         let mut cmd = Command::new(&self.toolchain.linker);
+        cmd.current_dir(&self.root);
         cmd.arg("-shared");
         objects.iter().for_each(|o| {
             cmd.arg(o);
@@ -268,47 +240,7 @@ impl ToolchainExecutor for BuildSystem {
             cmd.arg(format!("-O{}", self.profile_config.opt_level));
         }
 
-        for lib_dir in &self.package_config.lib_dirs {
-            if self.logger.verbose {
-                self.logger.log(
-                    LogLevel::Dim,
-                    &format!("-L '{}' (from crow.toml)", lib_dir),
-                    2,
-                );
-            }
-            cmd.arg(format!("-L{}", lib_dir));
-        }
-        for (name, build_output) in &self.dep_build_outputs {
-            if self.logger.verbose {
-                self.logger.log(
-                    LogLevel::Dim,
-                    &format!(
-                        "-L '{}' (from dependency '{name}')",
-                        build_output.library_dir.display()
-                    ),
-                    2,
-                );
-            }
-            cmd.arg(format!("-L{}", build_output.library_dir.display()));
-        }
-
-        for lib in &self.package_config.libs {
-            if self.logger.verbose {
-                self.logger
-                    .log(LogLevel::Dim, &format!("-l '{}' (from crow.toml)", lib), 2);
-            }
-            cmd.arg(format!("-l{}", lib));
-        }
-        for (name, build_output) in &self.dep_build_outputs {
-            if self.logger.verbose {
-                self.logger.log(
-                    LogLevel::Dim,
-                    &format!("-l '{}' (from dependency '{name}')", build_output.lib_name),
-                    2,
-                );
-            }
-            cmd.arg(format!("-l{}", build_output.lib_name));
-        }
+        self.append_link_inputs(&mut cmd);
 
         cmd.arg("-o").arg(output);
 
@@ -399,7 +331,7 @@ impl ToolchainExecutor for BuildSystem {
                     vec![format!("lib{}.so", name)]
                 }
             }
-            OutputType::Executable => {
+            OutputType::Executable | OutputType::HeaderOnly => {
                 return None;
             }
         };
@@ -441,3 +373,72 @@ impl ToolchainExecutor for BuildSystem {
         None
     }
 }
+
+impl BuildSystem {
+    fn append_link_inputs(&self, cmd: &mut Command) {
+        let dependencies = DependencyBuildOutput::flatten(self.dep_build_outputs.values());
+
+        for lib_dir in &self.package_config.lib_dirs {
+            if self.logger.verbose {
+                self.logger.log(
+                    LogLevel::Dim,
+                    format!("-L '{}' (from crow.toml)", lib_dir),
+                    2,
+                );
+            }
+            cmd.arg(format!("-L{}", lib_dir));
+        }
+        for build_output in &dependencies {
+            // Header-only outputs have no library of their own, only exported libs.
+            let library_dir = build_output
+                .library_path
+                .as_ref()
+                .map(|_| &build_output.library_dir);
+            let lib_dirs = library_dir.into_iter().chain(&build_output.lib_dirs);
+            for lib_dir in lib_dirs {
+                if self.logger.verbose {
+                    self.logger.log(
+                        LogLevel::Dim,
+                        format!(
+                            "-L '{}' (from dependency '{}')",
+                            lib_dir.display(),
+                            build_output.lib_name
+                        ),
+                        2,
+                    );
+                }
+                cmd.arg(format!("-L{}", lib_dir.display()));
+            }
+        }
+
+        for lib in &self.package_config.libs {
+            if self.logger.verbose {
+                self.logger
+                    .log(LogLevel::Dim, format!("-l '{}' (from crow.toml)", lib), 2);
+            }
+            cmd.arg(format!("-l{}", lib));
+        }
+        // Dependencies are ordered so that static libraries come before the libraries
+        // they depend on; each one is followed by the extra libs it exports.
+        for build_output in &dependencies {
+            let lib_name = build_output
+                .library_path
+                .as_ref()
+                .map(|_| &build_output.lib_name);
+            let libs = lib_name.into_iter().chain(&build_output.libs);
+            for lib in libs {
+                if self.logger.verbose {
+                    self.logger.log(
+                        LogLevel::Dim,
+                        format!("-l '{}' (from dependency '{}')", lib, build_output.lib_name),
+                        2,
+                    );
+                }
+                cmd.arg(format!("-l{}", lib));
+            }
+        }
+        for build_output in &dependencies {
+            cmd.args(&build_output.link_flags);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use super::*;
+use crate::build_system::{
+    CompilationDatabase, CompileCommand, DependencyBuildOutput, DependencyGraph,
+    DependencyRequester, DependencyResolver, DependencySource,
+};
+use crow_utils::logger::LogLevel;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    pub name: String,
+    pub path: PathBuf,
+    pub config: Config,
+}
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub config: Config,
+    pub members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+    /// Finds the workspace that `dir` belongs to: the nearest `crow.toml` with a
+    /// `[workspace]` table in `dir` or its parents, if `dir` is its root or a member.
+    pub fn discover(dir: &Path) -> anyhow::Result<Option<Self>> {
+        let dir = std::fs::canonicalize(dir)?;
+        for ancestor in dir.ancestors() {
+            let manifest = ancestor.join("crow.toml");
+            if !manifest.exists() {
+                continue;
+            }
+            let config = Config::load(&manifest)?;
+            if config.workspace.is_none() {
+                continue;
+            }
+
+            let workspace = Workspace::load(ancestor, config)?;
+            if workspace.root == dir || workspace.member_at(&dir).is_some() {
+                return Ok(Some(workspace));
+            }
+            return Ok(None);
+        }
+        Ok(None)
+    }
+
+    /// Directory holding `crow.lock` and `.crow/_deps` for the package at `dir`.
+    pub fn dependency_root(dir: &Path) -> anyhow::Result<PathBuf> {
+        Ok(Workspace::discover(dir)?.map_or_else(|| dir.to_path_buf(), |ws| ws.root))
+    }
+
+    fn load(root: &Path, config: Config) -> anyhow::Result<Self> {
+        let workspace_config = config.workspace.clone().unwrap_or_default();
+        let excluded: Vec<PathBuf> = workspace_config
+            .exclude
+            .iter()
+            .filter_map(|path| std::fs::canonicalize(root.join(path)).ok())
+            .collect();
+
+        let mut members = Vec::new();
+        // A root manifest with a [package] is a member itself.
+        if !config.package.name.is_empty() {
+            members.push(WorkspaceMember {
+                name: config.package.name.clone(),
+                path: root.to_path_buf(),
+                config: config.clone(),
+            });
+        }
+
+        for pattern in &workspace_config.members {
+            let full_pattern = root.join(pattern);
+            let mut matched = false;
+            for entry in glob::glob(&full_pattern.to_string_lossy())? {
+                let path = std::fs::canonicalize(entry?)?;
+                if !path.join("crow.toml").exists() || excluded.contains(&path) {
+                    continue;
+                }
+                matched = true;
+                if members.iter().any(|m: &WorkspaceMember| m.path == path) {
+                    continue;
+                }
+
+                let member_config = Config::load(path.join("crow.toml"))?;
+                if member_config.workspace.is_some() {
+                    anyhow::bail!(
+                        "Workspace member '{}' cannot define its own [workspace].",
+                        path.display()
+                    );
+                }
+                let name = member_config.package.name.clone();
+                if let Some(other) = members.iter().find(|m| m.name == name) {
+                    anyhow::bail!(
+                        "Workspace members '{}' and '{}' are both named '{}'.",
+                        other.path.display(),
+                        path.display(),
+                        name
+                    );
+                }
+                members.push(WorkspaceMember {
+                    name,
+                    path,
+                    config: member_config,
+                });
+            }
+            if !matched {
+                anyhow::bail!(
+                    "Workspace member pattern '{}' does not match any directory with a crow.toml.",
+                    pattern
+                );
+            }
+        }
+
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Workspace {
+            root: root.to_path_buf(),
+            config,
+            members,
+        })
+    }
+
+    /// Shared `target` directory of all members.
+    pub fn target_dir(&self) -> PathBuf {
+        self.root
+            .join(crow_utils::environment::Environment::build_dir())
+    }
+
+    pub fn member_at(&self, dir: &Path) -> Option<&WorkspaceMember> {
+        let dir = std::fs::canonicalize(dir).ok()?;
+        self.members.iter().find(|m| m.path == dir)
+    }
+
+    pub fn member(&self, name: &str) -> anyhow::Result<&WorkspaceMember> {
+        self.members.iter().find(|m| m.name == name).ok_or_else(|| {
+            let names: Vec<&str> = self.members.iter().map(|m| m.name.as_str()).collect();
+            anyhow::anyhow!(
+                "Package '{}' is not a member of the workspace. Members: {}",
+                name,
+                names.join(", ")
+            )
+        })
+    }
+
+    /// Every member contributes its dependencies, so the shared lockfile pins all of
+    /// them whichever member is built.
+    pub fn requesters(&self) -> Vec<DependencyRequester<'_>> {
+        self.members
+            .iter()
+            .map(|member| DependencyRequester {
+                name: member.name.clone(),
+                root: member.path.clone(),
+                dependencies: &member.config.dependencies,
+            })
+            .collect()
+    }
+
+    /// Builds the given members. Dependencies, including members used through path
+    /// dependencies, are built once from a single dependency graph, with the `[toolchain]`
+    /// and profile of the members that use them. Returns the output of each member and
+    /// writes one `compile_commands.json` for all of them.
+    pub fn build_members(
+        &self,
+        members: &[&WorkspaceMember],
+        profile: &str,
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<Vec<(String, PathBuf)>> {
+        // A virtual root builds nothing itself, but its hooks still run.
+        if self.member_at(&self.root).is_none() {
+            BuildSystem::resolve_config(&self.config, &self.root, profile, logger.clone())?;
+        }
+        let mut build_systems = Vec::new();
+        for member in members {
+            build_systems.push(BuildSystem::with_dependencies(
+                member.config.clone(),
+                &member.path,
+                profile,
+                global_deps,
+                offline,
+                IndexMap::new(),
+                IndexMap::new(),
+                logger.clone(),
+            )?);
+        }
+
+        let mut graph = DependencyGraph::default();
+        let mut built = IndexMap::new();
+        if members.iter().any(|m| !m.config.dependencies.is_empty()) {
+            logger.log(LogLevel::Bold, "Checking dependencies...", 1);
+            graph = DependencyGraph::collect(
+                &self.root,
+                &self.requesters(),
+                jobs,
+                global_deps,
+                offline,
+                logger.clone(),
+            )?;
+            built = self.build_dependencies(
+                &graph,
+                members,
+                &build_systems,
+                profile,
+                jobs,
+                global_deps,
+                offline,
+                logger.clone(),
+            )?;
+            logger.log(LogLevel::Bold, "Dependencies checked.", 1);
+        }
+
+        let mut outputs = Vec::new();
+        let mut compile_commands: Vec<CompileCommand> = Vec::new();
+        for (member, mut build_system) in members.iter().zip(build_systems) {
+            let dependencies = member.config.dependencies.keys();
+            build_system.downloaded_deps_paths = dependencies
+                .clone()
+                .map(|name| (name.clone(), graph.nodes[name].source_path.clone()))
+                .collect();
+            build_system.dep_build_outputs = dependencies
+                .map(|name| (name.clone(), built[name].clone()))
+                .collect();
+
+            // A member another member depends on was already built as a dependency. Its
+            // sources may have been copied, so it is matched by the path it was declared with.
+            let shared = graph.nodes.values().find(|node| {
+                matches!(&node.source, DependencySource::Path(path) if *path == member.path)
+                    && node.build_config.output_type == member.config.package.output_type
+            });
+            let output = match shared {
+                Some(node) => {
+                    logger.log(
+                        LogLevel::Info,
+                        format!(
+                            "[SHARED] Package `{}` was built as a dependency.",
+                            member.name
+                        ),
+                        1,
+                    );
+                    let output = &built[&node.name];
+                    output
+                        .library_path
+                        .clone()
+                        .unwrap_or_else(|| output.library_dir.clone())
+                }
+                None => build_system.build(jobs)?,
+            };
+            compile_commands.extend(build_system.compile_commands()?);
+            outputs.push((member.name.clone(), output));
+        }
+
+        let compdb_path = self
+            .target_dir()
+            .join(profile)
+            .join("compile_commands.json");
+        std::fs::create_dir_all(self.target_dir().join(profile))?;
+        std::fs::write(
+            &compdb_path,
+            serde_json::to_string_pretty(&compile_commands)?,
+        )?;
+        Ok(outputs)
+    }
+
+    /// Builds the dependencies of `members` once for each toolchain and profile the
+    /// members resolve to. A dependency shared by members that resolve differently is
+    /// an error, as only one build of it is kept.
+    #[allow(clippy::too_many_arguments)]
+    fn build_dependencies(
+        &self,
+        graph: &DependencyGraph,
+        members: &[&WorkspaceMember],
+        build_systems: &[BuildSystem],
+        profile: &str,
+        jobs: Option<usize>,
+        global_deps: bool,
+        offline: bool,
+        logger: Logger,
+    ) -> anyhow::Result<IndexMap<String, DependencyBuildOutput>> {
+        // (build system of the first member, member names, dependencies they declare)
+        let mut groups: Vec<(&BuildSystem, Vec<&str>, Vec<String>)> = Vec::new();
+        for (member, build_system) in members.iter().zip(build_systems) {
+            let index = match groups.iter().position(|(other, _, _)| {
+                other.toolchain == build_system.toolchain
+                    && other.profile_config == build_system.profile_config
+            }) {
+                Some(index) => index,
+                None => {
+                    groups.push((build_system, Vec::new(), Vec::new()));
+                    groups.len() - 1
+                }
+            };
+            let (_, names, roots) = &mut groups[index];
+            names.push(&member.name);
+            for name in member.config.dependencies.keys() {
+                if !roots.contains(name) {
+                    roots.push(name.clone());
+                }
+            }
+        }
+
+        let mut built_by: IndexMap<&str, usize> = IndexMap::new();
+        for (index, (_, names, roots)) in groups.iter().enumerate() {
+            for node in graph.build_order(roots)? {
+                if matches!(node.source, DependencySource::System { .. }) {
+                    continue;
+                }
+                if let Some(other) = built_by.insert(&node.name, index) {
+                    if other != index {
+                        anyhow::bail!(
+                            "Workspace members '{}' and '{}' use dependency '{}' with different [toolchain] or profile settings. Give them the same settings, or build them separately.",
+                            groups[other].1[0],
+                            names[0],
+                            node.name
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut built = IndexMap::new();
+        for (build_system, _, roots) in &groups {
+            if roots.is_empty() {
+                continue;
+            }
+            built.extend(BuildSystem::build_dependency_graph(
+                graph,
+                roots,
+                &build_system.toolchain,
+                profile,
+                &build_system.profile_config,
+                jobs,
+                global_deps,
+                offline,
+                logger.clone(),
+            )?);
+        }
+        Ok(built)
+    }
+}
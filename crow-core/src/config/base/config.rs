@@ -1,21 +1,26 @@
 use crate::PackageConfig;
 use anyhow::Context;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 use crate::dependency::Dependency;
 use crate::profile::BuildProfile;
 use crate::target::Target;
+use crate::test::TestTarget;
 use crate::toolchain::toolchain_config::ToolchainConfig;
+use crate::workspace::WorkspaceConfig;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct Config {
     pub package: PackageConfig,
     pub toolchain: ToolchainConfig,
-    pub profiles: Option<HashMap<String, BuildProfile>>,
-    pub targets: HashMap<String, Target>,
-    pub dependencies: HashMap<String, Dependency>,
+    pub profiles: Option<IndexMap<String, BuildProfile>>,
+    pub targets: IndexMap<String, Target>,
+    pub dependencies: IndexMap<String, Dependency>,
+    #[serde(rename = "test")]
+    pub tests: Vec<TestTarget>,
+    pub workspace: Option<WorkspaceConfig>,
 }
 
 impl Default for Config {
@@ -24,8 +29,10 @@ impl Default for Config {
             package: PackageConfig::default(),
             toolchain: ToolchainConfig::default(),
             profiles: Some(Config::generate_default_profiles_map()),
-            targets: HashMap::new(),
-            dependencies: HashMap::new(),
+            targets: IndexMap::new(),
+            dependencies: IndexMap::new(),
+            tests: Vec::new(),
+            workspace: None,
         }
     }
 }
@@ -56,8 +63,8 @@ impl Config {
         Ok(config)
     }
 
-    fn generate_default_profiles_map() -> HashMap<String, BuildProfile> {
-        let mut profiles = HashMap::new();
+    fn generate_default_profiles_map() -> IndexMap<String, BuildProfile> {
+        let mut profiles = IndexMap::new();
         profiles.insert("debug".to_string(), BuildProfile::default_debug());
         profiles.insert("release".to_string(), BuildProfile::default_release());
         profiles
@@ -0,0 +1,281 @@
+use crate::config::Config;
+use crate::dependency::{CrowDependencyBuild, Dependency};
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, Value};
+
+/// Edits `crow.toml` in place. Only the touched `[dependencies]` entries change; comments
+/// and layout of the rest of the file are kept.
+pub struct ManifestEditor {
+    path: PathBuf,
+    original: String,
+    document: DocumentMut,
+}
+
+impl ManifestEditor {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let original = std::fs::read_to_string(&path)
+            .with_context(|| "Cannot load `crow.toml`. Is it crow project?")?;
+        let document = original
+            .parse::<DocumentMut>()
+            .with_context(|| format!("Cannot parse '{}'", path.display()))?;
+        Ok(ManifestEditor {
+            path,
+            original,
+            document,
+        })
+    }
+
+    pub fn has_dependency(&self, name: &str) -> bool {
+        self.document
+            .get("dependencies")
+            .and_then(Item::as_table_like)
+            .is_some_and(|deps| deps.contains_key(name))
+    }
+
+    pub fn add_dependency(&mut self, name: &str, dependency: &Dependency) -> anyhow::Result<()> {
+        let dependencies = self
+            .document
+            .entry("dependencies")
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow::anyhow!("`dependencies` in crow.toml is not a table."))?;
+
+        let mut entry = Value::InlineTable(dependency_to_inline_table(dependency));
+        match dependencies.get_mut(name) {
+            // Keep the position and surrounding comments of an existing entry.
+            Some(item) => {
+                if let Some(old) = item.as_value() {
+                    *entry.decor_mut() = old.decor().clone();
+                }
+                *item = Item::Value(entry);
+            }
+            None => {
+                dependencies.insert(name, Item::Value(entry));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_dependency(&mut self, name: &str) -> anyhow::Result<()> {
+        let removed = self
+            .document
+            .get_mut("dependencies")
+            .and_then(Item::as_table_like_mut)
+            .and_then(|deps| deps.remove(name));
+        if removed.is_none() {
+            anyhow::bail!("Dependency '{}' not found in crow.toml.", name);
+        }
+        Ok(())
+    }
+
+    /// Writes the edited manifest and checks that it still loads. On failure the
+    /// original file is restored.
+    pub fn save(&self) -> anyhow::Result<Config> {
+        std::fs::write(&self.path, self.document.to_string())?;
+        match Config::load(&self.path) {
+            Ok(config) => Ok(config),
+            Err(e) => {
+                std::fs::write(&self.path, &self.original)?;
+                Err(e).with_context(|| "The edited crow.toml is invalid; it was not changed")
+            }
+        }
+    }
+}
+
+fn dependency_to_inline_table(dependency: &Dependency) -> InlineTable {
+    let mut table = InlineTable::new();
+    let build = match dependency {
+        Dependency::Git {
+            git,
+            branch,
+            tag,
+            rev,
+            submodules,
+            build,
+        } => {
+            table.insert("git", git.into());
+            if !branch.is_empty() {
+                table.insert("branch", branch.into());
+            }
+            if let Some(tag) = tag {
+                table.insert("tag", tag.into());
+            }
+            if let Some(rev) = rev {
+                table.insert("rev", rev.into());
+            }
+            if *submodules {
+                table.insert("submodules", true.into());
+            }
+            build
+        }
+        Dependency::Path { path, build } => {
+            table.insert("path", path.into());
+            build
+        }
+        Dependency::System { system, version } => {
+            table.insert("system", system.into());
+            if let Some(version) = version {
+                table.insert("version", version.into());
+            }
+            &None
+        }
+    };
+
+    if let Some(build) = build {
+        let build_table = build_to_inline_table(build);
+        if !build_table.is_empty() {
+            table.insert("build", Value::InlineTable(build_table));
+        }
+    }
+    table.fmt();
+    table
+}
+
+fn build_to_inline_table(build: &CrowDependencyBuild) -> InlineTable {
+    let defaults = CrowDependencyBuild::default();
+    let mut table = InlineTable::new();
+    if build.output_type != defaults.output_type {
+        table.insert("output_type", build.output_type.to_string().into());
+    }
+    if let Some(build_system) = &build.build_system {
+        table.insert("build_system", build_system.to_string().into());
+    }
+    if !build.cmake_options.is_empty() {
+        table.insert(
+            "cmake_options",
+            Value::Array(build.cmake_options.iter().collect::<Array>()),
+        );
+    }
+    if !build.targets.is_empty() {
+        table.insert(
+            "targets",
+            Value::Array(build.targets.iter().collect::<Array>()),
+        );
+    }
+    if build.lib_name != defaults.lib_name {
+        table.insert("lib_name", build.lib_name.as_str().into());
+    }
+    if !build.pch_headers.is_empty() {
+        table.insert(
+            "pch_headers",
+            Value::Array(build.pch_headers.iter().collect::<Array>()),
+        );
+    }
+    if !build.includes.is_empty() {
+        table.insert(
+            "includes",
+            Value::Array(build.includes.iter().collect::<Array>()),
+        );
+    }
+    if !build.defines.is_empty() {
+        table.insert(
+            "defines",
+            Value::Array(build.defines.iter().collect::<Array>()),
+        );
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputType;
+
+    const MANIFEST: &str = r#"# My package
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+# Logging
+log = { path = "../log" }
+net = { path = "../net" } # networking
+"#;
+
+    fn editor(test: &str) -> ManifestEditor {
+        let dir = std::env::temp_dir().join(format!("crow-manifest-{}-{test}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("crow.toml");
+        std::fs::write(&path, MANIFEST).unwrap();
+        ManifestEditor::open(&path).unwrap()
+    }
+
+    fn remove(editor: ManifestEditor) {
+        std::fs::remove_dir_all(editor.path.parent().unwrap()).unwrap();
+    }
+
+    fn git(build: Option<CrowDependencyBuild>) -> Dependency {
+        Dependency::Git {
+            git: "https://github.com/fmtlib/fmt".to_string(),
+            branch: String::new(),
+            tag: Some("10.2.1".to_string()),
+            rev: None,
+            submodules: false,
+            build,
+        }
+    }
+
+    #[test]
+    fn added_entry_is_written_inline_and_loads() {
+        let mut editor = editor("add");
+        assert!(!editor.has_dependency("fmt"));
+        let build = CrowDependencyBuild {
+            output_type: OutputType::SharedLib,
+            lib_name: "fmtd".to_string(),
+            ..Default::default()
+        };
+        editor.add_dependency("fmt", &git(Some(build))).unwrap();
+        let config = editor.save().unwrap();
+
+        let written = std::fs::read_to_string(&editor.path).unwrap();
+        assert!(written.starts_with(MANIFEST));
+        assert!(written.ends_with(
+            "fmt = { git = \"https://github.com/fmtlib/fmt\", tag = \"10.2.1\", build = { output_type = \"shared-lib\", lib_name = \"fmtd\" } }\n"
+        ));
+        let names: Vec<&String> = config.dependencies.keys().collect();
+        assert_eq!(names, ["log", "net", "fmt"]);
+        remove(editor);
+    }
+
+    #[test]
+    fn replaced_entry_keeps_position_and_comments() {
+        let mut editor = editor("replace");
+        editor.add_dependency("log", &git(None)).unwrap();
+        editor.save().unwrap();
+
+        let written = std::fs::read_to_string(&editor.path).unwrap();
+        assert_eq!(
+            written,
+            MANIFEST.replace(
+                "log = { path = \"../log\" }",
+                "log = { git = \"https://github.com/fmtlib/fmt\", tag = \"10.2.1\" }"
+            )
+        );
+        remove(editor);
+    }
+
+    #[test]
+    fn default_build_options_are_left_out() {
+        let table = dependency_to_inline_table(&git(Some(CrowDependencyBuild::default())));
+        assert_eq!(
+            table.to_string(),
+            "{ git = \"https://github.com/fmtlib/fmt\", tag = \"10.2.1\" }"
+        );
+    }
+
+    #[test]
+    fn removing_a_missing_dependency_fails() {
+        let mut editor = editor("remove");
+        editor.remove_dependency("net").unwrap();
+        assert!(!editor.has_dependency("net"));
+        assert!(editor.remove_dependency("net").is_err());
+        editor.save().unwrap();
+
+        let written = std::fs::read_to_string(&editor.path).unwrap();
+        assert!(!written.contains("net"));
+        assert!(written.contains("# Logging\nlog = { path = \"../log\" }\n"));
+        remove(editor);
+    }
+}
@@ -1,7 +1,11 @@
 pub mod config;
 pub mod final_config;
+pub mod manifest_editor;
 pub mod package_config;
+pub mod package_exports;
 
 pub use config::*;
 pub use final_config::*;
+pub use manifest_editor::*;
 pub use package_config::*;
+pub use package_exports::*;
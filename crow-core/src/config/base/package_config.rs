@@ -1,4 +1,5 @@
 use crate::output_type::OutputType;
+use crate::PackageExports;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -8,9 +9,11 @@ pub struct PackageConfig {
     pub version: String,
     pub output_type: OutputType,
     pub sources: Vec<String>,
+    pub exclude: Vec<String>,
     pub includes: Vec<String>,
     pub libs: Vec<String>,
     pub lib_dirs: Vec<String>,
+    pub exports: PackageExports,
 }
 
 impl PackageConfig {
@@ -30,9 +33,11 @@ impl Default for PackageConfig {
             version: String::new(),
             output_type: OutputType::default(),
             sources: Self::default_sources(),
+            exclude: Vec::new(),
             includes: Self::default_includes(),
             libs: Vec::new(),
             lib_dirs: Vec::new(),
+            exports: PackageExports::default(),
         }
     }
 }
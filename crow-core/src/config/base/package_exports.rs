@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct PackageExports {
+    pub includes: Option<Vec<String>>,
+    pub defines: Vec<String>,
+    pub libs: Vec<String>,
+    pub lib_dirs: Vec<String>,
+    pub link_flags: Vec<String>,
+}
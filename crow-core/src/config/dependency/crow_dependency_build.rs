@@ -1,3 +1,4 @@
+use crate::build_system::DependencyBuilders;
 use crate::build_system_type::BuildSystemType;
 use crate::output_type::OutputType;
 use serde::{Deserialize, Serialize};
@@ -8,8 +9,14 @@ pub struct CrowDependencyBuild {
     pub output_type: OutputType,
     pub build_system: Option<BuildSystemType>,
     pub cmake_options: Vec<String>,
+    /// CMake targets to build and link. Empty means the target named `lib_name`.
+    pub targets: Vec<String>,
     pub lib_name: String,
     pub pch_headers: Vec<String>,
+    /// Include paths of a header-only dependency, relative to its root.
+    pub includes: Vec<String>,
+    /// Definitions a header-only dependency passes to its consumers.
+    pub defines: Vec<String>,
 }
 
 impl Default for CrowDependencyBuild {
@@ -18,8 +25,11 @@ impl Default for CrowDependencyBuild {
             output_type: Self::default_output_type(),
             build_system: None,
             cmake_options: Vec::new(),
+            targets: Vec::new(),
             lib_name: Self::default_lib_name_placeholder(),
             pch_headers: Vec::new(),
+            includes: Vec::new(),
+            defines: Vec::new(),
         }
     }
 }
@@ -41,11 +51,18 @@ impl CrowDependencyBuild {
         let mut config = existing_config.unwrap_or_default();
 
         if config.build_system.is_none() {
-            if dep_path.join("crow.toml").exists() {
-                config.build_system = Some(BuildSystemType::Crow);
+            config.build_system = if dep_path.join("crow.toml").exists() {
+                Some(BuildSystemType::Crow)
+            } else if config.output_type == OutputType::HeaderOnly {
+                None
             } else {
-                config.build_system = Some(BuildSystemType::Cmake);
-            }
+                // Projects no builder recognizes are assumed to use CMake.
+                Some(
+                    DependencyBuilders::detect(dep_path)
+                        .map(|builder| BuildSystemType::from(builder.name().to_string()))
+                        .unwrap_or(BuildSystemType::Cmake),
+                )
+            };
         }
 
         if config.lib_name == CrowDependencyBuild::default_lib_name_placeholder() {
@@ -9,6 +9,12 @@ pub enum Dependency {
         #[serde(default = "Dependency::default_branch")]
         branch: String,
         #[serde(default)]
+        tag: Option<String>,
+        #[serde(default)]
+        rev: Option<String>,
+        #[serde(default)]
+        submodules: bool,
+        #[serde(default)]
         build: Option<CrowDependencyBuild>,
     },
     Path {
@@ -16,10 +22,24 @@ pub enum Dependency {
         #[serde(default)]
         build: Option<CrowDependencyBuild>,
     },
+    /// A library installed on the system, found through pkg-config.
+    System {
+        system: String,
+        #[serde(default)]
+        version: Option<String>,
+    },
 }
 
 impl Dependency {
     fn default_branch() -> String {
         "".to_string()
     }
+
+    pub fn build_config(&self) -> Option<&CrowDependencyBuild> {
+        match self {
+            Dependency::Git { build, .. } => build.as_ref(),
+            Dependency::Path { build, .. } => build.as_ref(),
+            Dependency::System { .. } => None,
+        }
+    }
 }
@@ -0,0 +1,124 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const LOCKFILE_HEADER: &str = "# This file is generated by CRow. Do not edit it by hand.\n";
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(rename = "package")]
+    pub packages: Vec<LockedDependency>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LockedDependency {
+    pub name: String,
+    pub git: String,
+    #[serde(default)]
+    pub branch: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tag: String,
+    pub rev: String,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Lockfile {
+            version: 1,
+            packages: Vec::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    pub const FILE_NAME: &'static str = "crow.lock";
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read lockfile '{}'", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Lockfile '{}' is corrupted", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let mut lockfile = self.clone();
+        lockfile.packages.sort_by(|a, b| a.name.cmp(&b.name));
+        let content = format!("{}{}", LOCKFILE_HEADER, toml::to_string(&lockfile)?);
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LockedDependency> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.packages.len();
+        self.packages.retain(|p| p.name != name);
+        len != self.packages.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(name: &str, tag: &str) -> LockedDependency {
+        LockedDependency {
+            name: name.to_string(),
+            git: format!("https://example.com/{name}.git"),
+            branch: String::new(),
+            tag: tag.to_string(),
+            rev: "4447c7562e3bc702ade25105912dce503f0c4010".to_string(),
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip_sorted_by_name() {
+        let dir = std::env::temp_dir().join(format!("crow-lockfile-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(Lockfile::FILE_NAME);
+
+        let lockfile = Lockfile {
+            version: 1,
+            packages: vec![locked("zlib", ""), locked("fmt", "10.2.1")],
+        };
+        lockfile.save(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(LOCKFILE_HEADER));
+        // An empty tag is not written.
+        assert_eq!(content.matches("tag =").count(), 1);
+
+        let loaded = Lockfile::load(&path).unwrap();
+        assert_eq!(
+            loaded.packages,
+            vec![locked("fmt", "10.2.1"), locked("zlib", "")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_lockfile_loads_empty() {
+        let lockfile = Lockfile::load("/nonexistent/crow.lock").unwrap();
+        assert_eq!(lockfile, Lockfile::default());
+    }
+
+    #[test]
+    fn remove_reports_whether_a_pin_was_dropped() {
+        let mut lockfile = Lockfile {
+            version: 1,
+            packages: vec![locked("fmt", "")],
+        };
+        assert!(lockfile.get("fmt").is_some());
+        assert!(lockfile.remove("fmt"));
+        assert!(!lockfile.remove("fmt"));
+        assert!(lockfile.get("fmt").is_none());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod lockfile;
+
+pub use lockfile::*;
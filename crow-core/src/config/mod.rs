@@ -1,13 +1,19 @@
 pub mod base;
 pub mod dependency;
+pub mod lock;
 pub mod profile;
 pub mod target;
+pub mod test;
 pub mod toolchain;
 pub mod types;
+pub mod workspace;
 
 pub use base::*;
 pub use dependency::*;
+pub use lock::*;
 pub use profile::*;
 pub use target::*;
+pub use test::*;
 pub use toolchain::*;
 pub use types::*;
+pub use workspace::*;
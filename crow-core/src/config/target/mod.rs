@@ -0,0 +1,3 @@
+pub mod target;
+
+pub use target::*;
@@ -0,0 +1,3 @@
+pub mod test_target;
+
+pub use test_target::*;
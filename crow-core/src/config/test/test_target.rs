@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct TestTarget {
+    pub name: String,
+    pub sources: Vec<String>,
+    pub includes: Vec<String>,
+    pub args: Vec<String>,
+}
@@ -1,12 +1,18 @@
 use crate::toolchain::toolchain_hooks::ToolchainHooks;
 use crate::toolchain::toolchain_override::ToolchainOverride;
+use crate::Language;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct ToolchainConfig {
     pub compiler: String,
     pub compiler_flags: Vec<String>,
+    pub c_compiler: String,
+    pub c_flags: Vec<String>,
+    pub cxx_flags: Vec<String>,
+    pub c_std: String,
+    pub cxx_std: String,
     pub linker: String,
     pub linker_flags: Vec<String>,
     pub archiver: String,
@@ -31,8 +37,8 @@ impl ToolchainConfig {
         }
     }
 
-    fn default_compiler_flags() -> Vec<String> {
-        vec!["-std=c++17".to_string()]
+    fn default_cxx_std() -> String {
+        "c++17".to_string()
     }
 
     fn default_linker_flags() -> Vec<String> {
@@ -57,6 +63,50 @@ impl ToolchainConfig {
         anyhow::bail!("Cannot find compiler in PATH.");
     }
 
+    /// Compiler for sources of `language`. Without an explicit `c_compiler`, C sources
+    /// use the C driver of the C++ compiler (`g++` -> `gcc`, `clang++` -> `clang`).
+    pub fn compiler_for(&self, language: Language) -> String {
+        match language {
+            Language::Cxx => self.compiler.clone(),
+            Language::C if !self.c_compiler.is_empty() => self.c_compiler.clone(),
+            Language::C => {
+                let compiler = &self.compiler;
+                if let Some(pos) = compiler.rfind("clang++") {
+                    format!("{}clang{}", &compiler[..pos], &compiler[pos + 7..])
+                } else if let Some(pos) = compiler.rfind("g++") {
+                    format!("{}gcc{}", &compiler[..pos], &compiler[pos + 3..])
+                } else if compiler.ends_with("c++") {
+                    format!("{}cc", &compiler[..compiler.len() - 3])
+                } else {
+                    compiler.clone()
+                }
+            }
+        }
+    }
+
+    /// `-std=` flag for sources of `language`. A bare version such as `17` becomes
+    /// `c17` or `c++17`.
+    pub fn std_flag(&self, language: Language) -> Option<String> {
+        let (std, prefix) = match language {
+            Language::C => (&self.c_std, "c"),
+            Language::Cxx => (&self.cxx_std, "c++"),
+        };
+        if std.is_empty() {
+            None
+        } else if std.chars().all(|c| c.is_ascii_digit()) {
+            Some(format!("-std={prefix}{std}"))
+        } else {
+            Some(format!("-std={std}"))
+        }
+    }
+
+    pub fn language_flags(&self, language: Language) -> &[String] {
+        match language {
+            Language::C => &self.c_flags,
+            Language::Cxx => &self.cxx_flags,
+        }
+    }
+
     pub fn merge(&self, override_config: Option<&ToolchainOverride>) -> Self {
         let Some(ov) = override_config else {
             return self.clone();
@@ -68,6 +118,11 @@ impl ToolchainConfig {
                 .compiler_flags
                 .clone()
                 .unwrap_or(self.compiler_flags.clone()),
+            c_compiler: ov.c_compiler.clone().unwrap_or(self.c_compiler.clone()),
+            c_flags: ov.c_flags.clone().unwrap_or(self.c_flags.clone()),
+            cxx_flags: ov.cxx_flags.clone().unwrap_or(self.cxx_flags.clone()),
+            c_std: ov.c_std.clone().unwrap_or(self.c_std.clone()),
+            cxx_std: ov.cxx_std.clone().unwrap_or(self.cxx_std.clone()),
             linker: ov.linker.clone().unwrap_or(self.linker.clone()),
             linker_flags: ov.linker_flags.clone().unwrap_or(self.linker_flags.clone()),
             archiver: ov.archiver.clone().unwrap_or(self.archiver.clone()),
@@ -98,7 +153,12 @@ impl Default for ToolchainConfig {
         ToolchainConfig {
             compiler,
             linker,
-            compiler_flags: ToolchainConfig::default_compiler_flags(),
+            compiler_flags: Vec::new(),
+            c_compiler: String::new(),
+            c_flags: Vec::new(),
+            cxx_flags: Vec::new(),
+            c_std: String::new(),
+            cxx_std: ToolchainConfig::default_cxx_std(),
             linker_flags: ToolchainConfig::default_linker_flags(),
             archiver: ToolchainConfig::default_archiver(),
             archiver_flags: ToolchainConfig::default_archiver_flags(),
@@ -109,3 +169,68 @@ impl Default for ToolchainConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toolchain(compiler: &str) -> ToolchainConfig {
+        ToolchainConfig {
+            compiler: compiler.to_string(),
+            ..ToolchainConfig::default()
+        }
+    }
+
+    #[test]
+    fn c_sources_use_the_c_driver_of_the_compiler() {
+        for (compiler, c_compiler) in [
+            ("g++", "gcc"),
+            ("clang++", "clang"),
+            ("clang++-17", "clang-17"),
+            (
+                "/usr/bin/aarch64-linux-gnu-g++",
+                "/usr/bin/aarch64-linux-gnu-gcc",
+            ),
+            ("c++", "cc"),
+            ("clang", "clang"),
+        ] {
+            assert_eq!(toolchain(compiler).compiler_for(Language::C), c_compiler);
+            assert_eq!(toolchain(compiler).compiler_for(Language::Cxx), compiler);
+        }
+    }
+
+    #[test]
+    fn explicit_c_compiler_wins() {
+        let toolchain = ToolchainConfig {
+            c_compiler: "tcc".to_string(),
+            ..toolchain("g++")
+        };
+        assert_eq!(toolchain.compiler_for(Language::C), "tcc");
+        assert_eq!(toolchain.compiler_for(Language::Cxx), "g++");
+    }
+
+    #[test]
+    fn std_flag_expands_bare_versions() {
+        let toolchain = ToolchainConfig {
+            c_std: "11".to_string(),
+            cxx_std: "gnu++20".to_string(),
+            ..toolchain("g++")
+        };
+        assert_eq!(toolchain.std_flag(Language::C).as_deref(), Some("-std=c11"));
+        assert_eq!(
+            toolchain.std_flag(Language::Cxx).as_deref(),
+            Some("-std=gnu++20")
+        );
+
+        let toolchain = ToolchainConfig {
+            c_std: String::new(),
+            cxx_std: "17".to_string(),
+            ..toolchain
+        };
+        assert_eq!(toolchain.std_flag(Language::C), None);
+        assert_eq!(
+            toolchain.std_flag(Language::Cxx).as_deref(),
+            Some("-std=c++17")
+        );
+    }
+}
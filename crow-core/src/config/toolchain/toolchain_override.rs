@@ -6,6 +6,11 @@ use serde::{Deserialize, Serialize};
 pub struct ToolchainOverride {
     pub compiler: Option<String>,
     pub compiler_flags: Option<Vec<String>>,
+    pub c_compiler: Option<String>,
+    pub c_flags: Option<Vec<String>>,
+    pub cxx_flags: Option<Vec<String>>,
+    pub c_std: Option<String>,
+    pub cxx_std: Option<String>,
     pub linker: Option<String>,
     pub linker_flags: Option<Vec<String>>,
     pub archiver: Option<String>,
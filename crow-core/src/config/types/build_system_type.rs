@@ -1,9 +1,45 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(from = "String", into = "String")]
 pub enum BuildSystemType {
-    #[serde(rename = "crow")]
     Crow,
-    #[serde(rename = "cmake")]
     Cmake,
+    Meson,
+    Autotools,
+    Make,
+    /// A build system handled by a builder registered with `DependencyBuilders`.
+    Custom(String),
+}
+
+impl From<String> for BuildSystemType {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "crow" => BuildSystemType::Crow,
+            "cmake" => BuildSystemType::Cmake,
+            "meson" => BuildSystemType::Meson,
+            "autotools" => BuildSystemType::Autotools,
+            "make" => BuildSystemType::Make,
+            _ => BuildSystemType::Custom(name),
+        }
+    }
+}
+
+impl From<BuildSystemType> for String {
+    fn from(build_system: BuildSystemType) -> Self {
+        build_system.to_string()
+    }
+}
+
+impl std::fmt::Display for BuildSystemType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildSystemType::Crow => write!(f, "crow"),
+            BuildSystemType::Cmake => write!(f, "cmake"),
+            BuildSystemType::Meson => write!(f, "meson"),
+            BuildSystemType::Autotools => write!(f, "autotools"),
+            BuildSystemType::Make => write!(f, "make"),
+            BuildSystemType::Custom(name) => write!(f, "{name}"),
+        }
+    }
 }
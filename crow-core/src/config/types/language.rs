@@ -0,0 +1,49 @@
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    C,
+    Cxx,
+}
+
+impl Language {
+    /// Only a lowercase `.c` extension is C. The extension is matched case
+    /// sensitively because `.C` is the traditional C++ extension; every other
+    /// source, whatever its case, is compiled as C++.
+    pub fn from_source(source: &Path) -> Self {
+        match source.extension().and_then(|ext| ext.to_str()) {
+            Some("c") => Language::C,
+            Some("C") => Language::Cxx,
+            _ => Language::Cxx,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowercase_c_is_c() {
+        assert_eq!(Language::from_source(Path::new("src/main.c")), Language::C);
+        assert_eq!(
+            Language::from_source(Path::new("src/archive.tar.c")),
+            Language::C
+        );
+    }
+
+    #[test]
+    fn uppercase_c_is_cxx() {
+        assert_eq!(
+            Language::from_source(Path::new("src/main.C")),
+            Language::Cxx
+        );
+    }
+
+    #[test]
+    fn other_extensions_are_cxx_in_any_case() {
+        for source in ["a.cpp", "a.CPP", "a.cc", "a.CC", "a.cxx", "a.c++", "a"] {
+            assert_eq!(Language::from_source(Path::new(source)), Language::Cxx);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod build_system_type;
+pub mod language;
 pub mod output_type;
 
 pub use build_system_type::*;
+pub use language::*;
 pub use output_type::*;
@@ -8,6 +8,8 @@ pub enum OutputType {
     StaticLib,
     #[serde(rename = "shared-lib")]
     SharedLib,
+    #[serde(rename = "header-only")]
+    HeaderOnly,
 }
 
 impl Default for OutputType {
@@ -15,3 +17,14 @@ impl Default for OutputType {
         OutputType::Executable
     }
 }
+
+impl std::fmt::Display for OutputType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputType::Executable => write!(f, "executable"),
+            OutputType::StaticLib => write!(f, "static-lib"),
+            OutputType::SharedLib => write!(f, "shared-lib"),
+            OutputType::HeaderOnly => write!(f, "header-only"),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod workspace_config;
+
+pub use workspace_config::*;
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    pub members: Vec<String>,
+    pub exclude: Vec<String>,
+}
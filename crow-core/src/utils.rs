@@ -1,14 +1,159 @@
 use crate::config::PackageConfig;
 use anyhow::Result;
-use glob::glob;
-use std::path::PathBuf;
+use glob::{glob, MatchOptions, Pattern};
+use std::path::{Path, PathBuf};
 
-pub fn find_source_files(package_config: &PackageConfig) -> Result<Vec<PathBuf>> {
-    let mut sources: Vec<PathBuf> = Vec::new();
+/// Source files of a package, relative to `root` (the directory of its `crow.toml`).
+/// Patterns in `sources` starting with `!` and all patterns in `exclude` remove files;
+/// an exclusion that matches a directory removes everything below it.
+pub fn find_source_files(package_config: &PackageConfig, root: &Path) -> Result<Vec<PathBuf>> {
+    let mut includes = Vec::new();
+    let mut excludes = Vec::new();
     for pattern in &package_config.sources {
-        for entry in glob(pattern)? {
-            sources.push(entry?);
+        match pattern.strip_prefix('!') {
+            Some(excluded) => excludes.push(exclude_pattern(excluded)?),
+            None => includes.push(pattern),
+        }
+    }
+    for pattern in &package_config.exclude {
+        excludes.push(exclude_pattern(pattern)?);
+    }
+
+    let escaped_root = PathBuf::from(Pattern::escape(&root.to_string_lossy()));
+    let mut sources: Vec<PathBuf> = Vec::new();
+    for pattern in includes {
+        for entry in glob(&escaped_root.join(pattern).to_string_lossy())? {
+            let path = entry?;
+            let path = path
+                .strip_prefix(root)
+                .map_or(path.clone(), Path::to_path_buf);
+            if is_excluded(&path, &excludes) || sources.contains(&path) {
+                continue;
+            }
+            sources.push(path);
         }
     }
     Ok(sources)
 }
+
+fn exclude_pattern(pattern: &str) -> Result<Pattern> {
+    let pattern = pattern.trim_end_matches('/');
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    Ok(Pattern::new(pattern)?)
+}
+
+fn is_excluded(path: &Path, excludes: &[Pattern]) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    path.ancestors()
+        .filter(|p| !p.as_os_str().is_empty())
+        .any(|p| excludes.iter().any(|e| e.matches_path_with(p, options)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package_root(name: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("crow-sources-{name}-{}", std::process::id()));
+        for file in files {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        root
+    }
+
+    fn sources(root: &Path, sources: &[&str], exclude: &[&str]) -> Vec<PathBuf> {
+        let package_config = PackageConfig {
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+            ..PackageConfig::default()
+        };
+        let mut found = find_source_files(&package_config, root).unwrap();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn negated_sources_and_exclude_remove_files() {
+        let root = package_root(
+            "negated",
+            &[
+                "src/main.c",
+                "src/legacy.c",
+                "src/platform/win.c",
+                "src/platform/unix.c",
+            ],
+        );
+        let expected = vec![
+            PathBuf::from("src/main.c"),
+            PathBuf::from("src/platform/unix.c"),
+        ];
+        assert_eq!(
+            sources(
+                &root,
+                &["src/**/*.c", "!src/legacy.c", "!src/platform/win.c"],
+                &[]
+            ),
+            expected
+        );
+        assert_eq!(
+            sources(
+                &root,
+                &["src/**/*.c"],
+                &["src/legacy.c", "src/platform/win.c"]
+            ),
+            expected
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn excluded_directory_removes_everything_below() {
+        let root = package_root(
+            "directory",
+            &[
+                "src/main.c",
+                "src/third_party/a.c",
+                "src/third_party/deep/b.c",
+            ],
+        );
+        let expected = vec![PathBuf::from("src/main.c")];
+        assert_eq!(
+            sources(&root, &["src/**/*.c"], &["src/third_party"]),
+            expected
+        );
+        assert_eq!(
+            sources(&root, &["src/**/*.c"], &["./src/third_party/"]),
+            expected
+        );
+        assert_eq!(
+            sources(&root, &["src/**/*.c", "!src/third_party/"], &[]),
+            expected
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn wildcards_do_not_cross_directories() {
+        let root = package_root("separator", &["src/a_test.c", "src/nested/b_test.c"]);
+        assert_eq!(
+            sources(&root, &["src/**/*.c"], &["src/*_test.c"]),
+            vec![PathBuf::from("src/nested/b_test.c")]
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn overlapping_patterns_list_a_file_once() {
+        let root = package_root("overlap", &["src/main.c"]);
+        assert_eq!(
+            sources(&root, &["src/*.c", "src/**/*.c", "src/main.c"], &[]),
+            vec![PathBuf::from("src/main.c")]
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -31,6 +31,14 @@ impl Environment {
             .unwrap_or(global_deps)
     }
 
+    /// `--offline` cannot be turned off by the environment; `CROW_OFFLINE` can only
+    /// turn offline mode on.
+    pub fn offline(offline: bool) -> bool {
+        offline
+            || env::var("CROW_OFFLINE")
+                .is_ok_and(|val| val == "1" || val.eq_ignore_ascii_case("true"))
+    }
+
     pub fn quiet_mode(quiet_mode: bool) -> bool {
         env::var("CROW_QUIET_MODE")
             .map(|val| val.eq_ignore_ascii_case("true"))